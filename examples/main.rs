@@ -1,20 +1,16 @@
-use std::str::FromStr;
-
 use aptos_sdk::{rest_client::{Client, FaucetClient, aptos_api_types::U64}, types::LocalAccount, coin_client::CoinClient};
 use once_cell::sync::Lazy;
-use rust_aptos_token_client::{types::{TokenId, TokenDataId}, TokenClient};
+use rust_aptos_token_client::{network::Network, requests::{CreateCollectionRequest, CreateTokenRequest}, types::{TokenId, TokenDataId}, TokenClient};
 use anyhow::{Context, Result};
-use url::Url;
 
-static NODE_URL: Lazy<Url> = Lazy::new(||  Url::from_str("https://fullnode.devnet.aptoslabs.com").unwrap());
-static FAUCET_URL: Lazy<Url> = Lazy::new(|| Url::from_str("https://faucet.devnet.aptoslabs.com").unwrap());
+static NETWORK: Lazy<Network> = Lazy::new(|| Network::Devnet);
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let rest_client = Client::new(NODE_URL.clone());
-    let faucet_client = FaucetClient::new(FAUCET_URL.clone(), NODE_URL.clone());
+    let rest_client = Client::new(NETWORK.node_url());
+    let faucet_client = FaucetClient::new(NETWORK.faucet_url().unwrap(), NETWORK.node_url());
     let coin_client = CoinClient::new(&rest_client);
-    let token_client = TokenClient::new(&rest_client).await.context("Failed to create token client")?;
+    let token_client = TokenClient::from_network(&rest_client, &NETWORK).await.context("Failed to create token client")?;
 
     let mut alice = LocalAccount::generate(&mut rand::rngs::OsRng);
     let mut bob = LocalAccount::generate(&mut rand::rngs::OsRng);
@@ -47,13 +43,15 @@ async fn main() -> Result<()> {
 
     println!("\n=== Creating NFT Collection ===");
 
+    let collection_request = CreateCollectionRequest::builder(collection_name)
+        .description("Example description")
+        .uri("uri here")
+        .maximum(1_00)
+        .build()?;
+
     let tx_hash = token_client.create_collection_script(
         &mut alice,
-        collection_name,
-        "Example description",
-        "uri here",
-        1_00, 
-        None,
+        &collection_request,
         None,
     ).await.context("Failed to submit create collection tx")?;
 
@@ -73,17 +71,16 @@ async fn main() -> Result<()> {
 
     println!("\n=== Creating NFT Token for Collection: `{}` ===", collection_name);
 
+    let token_request = CreateTokenRequest::builder(collection_name, token_name)
+        .description("First NFT Description")
+        .uri("First NFT URI")
+        .supply(1)
+        .maximum(1)
+        .build()?;
+
     let tx_hash = token_client.create_token(
         &mut alice,
-        collection_name,
-        token_name,
-        "First NFT Description",
-        1,
-        "First NFT URI",
-        1,
-        None,
-        None,
-        None,
+        &token_request,
         None,
     ).await.context("Failed to submit create token tx")?;
 
@@ -0,0 +1,301 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+        PrivateKey,
+    },
+    rest_client::Client as ApiClient,
+    types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey, LocalAccount},
+};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use url::Url;
+
+use crate::{history::try_get_table_item, offline::decode_hex};
+
+/// Derivation path used by the Aptos CLI and wallets.
+pub const APTOS_DERIVATION_PATH: &str = "m/44'/637'/0'/0'/0'";
+
+pub fn parse_address(address: &str) -> Result<AccountAddress> {
+    let address = address.trim();
+    let address = if address.starts_with("0x") {
+        AccountAddress::from_hex_literal(address)
+    } else {
+        AccountAddress::from_hex(address)
+    };
+    address.map_err(|e| anyhow!("Invalid account address: {}", e))
+}
+
+pub fn parse_private_key(private_key: &str) -> Result<Ed25519PrivateKey> {
+    Ed25519PrivateKey::try_from(decode_hex(private_key)?.as_slice())
+        .context("Invalid Ed25519 private key")
+}
+
+/// The address that `public_key` signs for. Accounts whose authentication
+/// key was rotated keep their original address, which the framework
+/// records in `0x1::account::OriginatingAddress`.
+pub async fn resolve_address(api_client: &ApiClient, public_key: &Ed25519PublicKey) -> Result<AccountAddress> {
+    let derived_address = AuthenticationKey::ed25519(public_key).derived_address();
+
+    let resource = api_client
+        .get_account_resource(AccountAddress::ONE, "0x1::account::OriginatingAddress")
+        .await
+        .context("Error on getting account resource <0x1::account::OriginatingAddress>")?
+        .into_inner();
+    let handle = match resource.and_then(|resource| resource.data["address_map"]["handle"].as_str().map(String::from)) {
+        Some(handle) => parse_address(&handle)?,
+        None => return Ok(derived_address),
+    };
+
+    let item = try_get_table_item(
+        &reqwest::Client::new(),
+        api_client,
+        handle,
+        "address",
+        "address",
+        derived_address.to_hex_literal(),
+        None,
+    )
+    .await
+    .context("Error on resolving originating address")?;
+    // Not found: the key was never rotated onto another account.
+    match item.as_ref().and_then(|item| item.as_str()) {
+        Some(address) => parse_address(address),
+        None => Ok(derived_address),
+    }
+}
+
+/// Builds a `LocalAccount` with its on-chain sequence number. Without an
+/// explicit `address`, it is resolved from the key via `resolve_address`.
+pub async fn load_account(
+    api_client: &ApiClient,
+    private_key: Ed25519PrivateKey,
+    address: Option<AccountAddress>,
+) -> Result<LocalAccount> {
+    let address = match address {
+        Some(address) => address,
+        None => resolve_address(api_client, &private_key.public_key()).await?,
+    };
+    let sequence_number = api_client
+        .get_account(address)
+        .await
+        .with_context(|| format!("Failed to get account {}", address.to_hex_literal()))?
+        .into_inner()
+        .sequence_number;
+
+    Ok(LocalAccount::new(address, private_key, sequence_number))
+}
+
+// Aptos CLI profiles
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CliProfile {
+    pub private_key: Option<String>,
+    pub public_key: Option<String>,
+    pub account: Option<String>,
+    pub rest_url: Option<Url>,
+    pub faucet_url: Option<Url>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CliConfig {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, CliProfile>,
+}
+
+impl CliConfig {
+    /// Reads an Aptos CLI `config.yaml`, usually `.aptos/config.yaml`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yaml::from_str(&content).context("Error on parsing Aptos CLI config")
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&CliProfile> {
+        self.profiles
+            .get(name)
+            .with_context(|| format!("Profile `{}` not found", name))
+    }
+}
+
+impl CliProfile {
+    pub fn address(&self) -> Result<Option<AccountAddress>> {
+        self.account.as_deref().map(parse_address).transpose()
+    }
+
+    pub async fn load_account(&self, api_client: &ApiClient) -> Result<LocalAccount> {
+        let private_key = parse_private_key(
+            self.private_key
+                .as_deref()
+                .context("Profile has no private key")?,
+        )?;
+        load_account(api_client, private_key, self.address()?).await
+    }
+}
+
+// BIP-39 mnemonics
+
+type HmacSha512 = Hmac<Sha512>;
+
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let mut output = [0u8; 64];
+    output.copy_from_slice(&mac.finalize().into_bytes());
+    output
+}
+
+/// SLIP-0010 derivation; Ed25519 only supports hardened indexes.
+fn derive_ed25519_key(seed: &[u8], path: &str) -> Result<[u8; 32]> {
+    let mut segments = path.split('/');
+    if segments.next() != Some("m") {
+        bail!("Derivation path must start with `m`: {}", path);
+    }
+
+    let mut node = hmac_sha512(b"ed25519 seed", seed);
+    for segment in segments {
+        let index: u32 = segment
+            .strip_suffix('\'')
+            .with_context(|| format!("Ed25519 derivation needs hardened indexes, got `{}`", segment))?
+            .parse()
+            .with_context(|| format!("Invalid derivation index `{}`", segment))?;
+
+        let mut data = Vec::with_capacity(37);
+        data.push(0u8);
+        data.extend_from_slice(&node[..32]);
+        data.extend_from_slice(&(index | HARDENED_OFFSET).to_be_bytes());
+        node = hmac_sha512(&node[32..], &data);
+    }
+
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&node[..32]);
+    Ok(key)
+}
+
+/// Derives the key at `derivation_path` from a BIP-39 seed.
+pub fn private_key_from_seed(seed: &[u8], derivation_path: Option<&str>) -> Result<Ed25519PrivateKey> {
+    let key = derive_ed25519_key(seed, derivation_path.unwrap_or(APTOS_DERIVATION_PATH))?;
+
+    Ed25519PrivateKey::try_from(&key[..]).context("Invalid derived private key")
+}
+
+pub fn private_key_from_mnemonic(mnemonic: &str, derivation_path: Option<&str>) -> Result<Ed25519PrivateKey> {
+    let mnemonic = bip39::Mnemonic::from_phrase(mnemonic.trim(), bip39::Language::English)
+        .map_err(|e| anyhow!("Invalid mnemonic: {}", e))?;
+    let seed = bip39::Seed::new(&mnemonic, "");
+
+    private_key_from_seed(seed.as_bytes(), derivation_path)
+}
+
+pub async fn load_account_from_mnemonic(
+    api_client: &ApiClient,
+    mnemonic: &str,
+    derivation_path: Option<&str>,
+    address: Option<AccountAddress>,
+) -> Result<LocalAccount> {
+    load_account(api_client, private_key_from_mnemonic(mnemonic, derivation_path)?, address).await
+}
+
+// Encrypted keystores
+
+const KEYSTORE_VERSION: u32 = 1;
+const KEYSTORE_NONCE_LENGTH: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    pub salt: String,
+}
+
+/// A private key encrypted with AES-256-GCM under a scrypt-derived key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Keystore {
+    pub version: u32,
+    pub address: AccountAddress,
+    pub scrypt: ScryptParams,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn keystore_cipher(password: &str, params: &ScryptParams) -> Result<Aes256Gcm> {
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p)
+        .map_err(|e| anyhow!("Invalid scrypt parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), &decode_hex(&params.salt)?, &scrypt_params, &mut key)
+        .map_err(|e| anyhow!("Failed to derive keystore key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("Invalid keystore key length"))
+}
+
+impl Keystore {
+    pub fn encrypt(address: AccountAddress, private_key: &Ed25519PrivateKey, password: &str) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        let mut nonce = [0u8; KEYSTORE_NONCE_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let scrypt = ScryptParams {
+            log_n: 15,
+            r: 8,
+            p: 1,
+            salt: hex::encode(salt),
+        };
+        let ciphertext = keystore_cipher(password, &scrypt)?
+            .encrypt(Nonce::from_slice(&nonce), private_key.to_bytes().as_ref())
+            .map_err(|_| anyhow!("Failed to encrypt private key"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            scrypt,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Ed25519PrivateKey> {
+        if self.version != KEYSTORE_VERSION {
+            bail!("Unsupported keystore version {}", self.version);
+        }
+        let nonce = decode_hex(&self.nonce)?;
+        if nonce.len() != KEYSTORE_NONCE_LENGTH {
+            bail!("Keystore nonce is {} bytes long, expected {}", nonce.len(), KEYSTORE_NONCE_LENGTH);
+        }
+        let plaintext = keystore_cipher(password, &self.scrypt)?
+            .decrypt(Nonce::from_slice(&nonce), decode_hex(&self.ciphertext)?.as_ref())
+            .map_err(|_| anyhow!("Wrong password or corrupted keystore"))?;
+
+        Ed25519PrivateKey::try_from(plaintext.as_slice()).context("Invalid private key in keystore")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).context("Error on parsing keystore")
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The keystore's address is used as is, so rotated accounts load
+    /// correctly.
+    pub async fn load_account(&self, api_client: &ApiClient, password: &str) -> Result<LocalAccount> {
+        load_account(api_client, self.decrypt(password)?, Some(self.address)).await
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{rest_client::aptos_api_types::U64, types::account_address::AccountAddress};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    journal::{JobOperation, JobState, Journal, JournalRunOptions, JournalSummary},
+    offers::OfferClosing,
+    signer::TransactionSigner,
+    snapshot::Snapshot,
+    types::{TokenDataId, TokenId, TokenOfferId, TransactionOptions},
+    TokenClient,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AirdropRecipient {
+    pub address: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: u64,
+}
+
+/// Reads a JSON array of recipients.
+pub fn load_recipients(path: impl AsRef<Path>) -> Result<Vec<AirdropRecipient>> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).context("Error on parsing airdrop recipients")
+}
+
+/// One recipient per snapshot holder, with the holder's balance.
+pub fn recipients_from_snapshot(snapshot: &Snapshot) -> Vec<AirdropRecipient> {
+    snapshot
+        .entries
+        .iter()
+        .map(|entry| AirdropRecipient {
+            address: entry.holder.owner,
+            token_id: entry.holder.token_id.clone(),
+            amount: entry.holder.amount,
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryMethod {
+    /// Co-signed by the receiver.
+    DirectTransfer,
+    /// The receiver opted in to direct transfers.
+    TransferWithOptIn,
+    /// The receiver claims the token later.
+    Offer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AirdropOutcome {
+    pub recipient: AirdropRecipient,
+    pub method: DeliveryMethod,
+    pub hash: Option<String>,
+    /// Why the delivery failed.
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AirdropReport {
+    pub delivered: Vec<AirdropOutcome>,
+    /// Offers still waiting for the receiver's claim.
+    pub offered_unclaimed: Vec<AirdropOutcome>,
+    /// Offers cancelled before the receiver claimed them.
+    pub cancelled: Vec<AirdropOutcome>,
+    pub failed: Vec<AirdropOutcome>,
+    /// Jobs not finished yet.
+    pub pending: Vec<AirdropOutcome>,
+}
+
+fn delivery(operation: &JobOperation) -> Option<(AirdropRecipient, DeliveryMethod)> {
+    let (receiver, creator, collection, name, amount, property_version, method) = match operation {
+        JobOperation::DirectTransfer { receiver, creator, collection, name, amount, property_version } => {
+            (receiver, creator, collection, name, amount, property_version, DeliveryMethod::DirectTransfer)
+        }
+        JobOperation::TransferWithOptIn { receiver, creator, collection, name, amount, property_version } => {
+            (receiver, creator, collection, name, amount, property_version, DeliveryMethod::TransferWithOptIn)
+        }
+        JobOperation::OfferToken { receiver, creator, collection, name, amount, property_version } => {
+            (receiver, creator, collection, name, amount, property_version, DeliveryMethod::Offer)
+        }
+        _ => return None,
+    };
+
+    let recipient = AirdropRecipient {
+        address: *receiver,
+        token_id: TokenId {
+            token_data_id: TokenDataId {
+                creator: *creator,
+                collection: collection.clone(),
+                name: name.clone(),
+            },
+            property_version: U64(*property_version),
+        },
+        amount: *amount,
+    };
+    Some((recipient, method))
+}
+
+/// Fails unless `journal` was planned for exactly `recipients`, in order.
+pub fn check_recipients(journal: &Journal, recipients: &[AirdropRecipient]) -> Result<()> {
+    let planned: Vec<AirdropRecipient> = journal
+        .jobs()
+        .iter()
+        .filter_map(|job| delivery(&job.operation))
+        .map(|(recipient, _)| recipient)
+        .collect();
+    if planned != recipients {
+        bail!(
+            "The airdrop journal was planned for {} other recipients; finish it with the same recipients or start a new journal",
+            planned.len()
+        );
+    }
+    Ok(())
+}
+
+impl<'a> TokenClient<'a> {
+    /// Opens the airdrop journal at `path` to resume it, or creates it
+    /// with `plan_airdrop_operations`. An existing journal must have been
+    /// planned for the same `recipients`.
+    pub async fn plan_airdrop(
+        &self,
+        path: impl AsRef<Path>,
+        recipients: &[AirdropRecipient],
+        co_signers: &HashSet<AccountAddress>,
+    ) -> Result<Journal> {
+        if path.as_ref().exists() {
+            let journal = Journal::open(path)?;
+            check_recipients(&journal, recipients)?;
+            return Ok(journal);
+        }
+        Journal::create(path, self.plan_airdrop_operations(recipients, co_signers).await?)
+    }
+
+    /// One job per recipient: a direct transfer when the receiver is in
+    /// `co_signers`, `transfer_with_opt_in` when the receiver opted in,
+    /// and an offer otherwise. Nothing is written.
+    pub async fn plan_airdrop_operations(
+        &self,
+        recipients: &[AirdropRecipient],
+        co_signers: &HashSet<AccountAddress>,
+    ) -> Result<Vec<JobOperation>> {
+        let mut operations = Vec::with_capacity(recipients.len());
+        for recipient in recipients {
+            let token_data_id = &recipient.token_id.token_data_id;
+            let receiver = recipient.address;
+            let creator = token_data_id.creator;
+            let collection = token_data_id.collection.clone();
+            let name = token_data_id.name.clone();
+            let amount = recipient.amount;
+            let property_version = recipient.token_id.property_version.0;
+
+            let operation = if co_signers.contains(&receiver) {
+                JobOperation::DirectTransfer { receiver, creator, collection, name, amount, property_version }
+            } else if self.is_direct_transfer_opted_in(receiver).await? {
+                JobOperation::TransferWithOptIn { receiver, creator, collection, name, amount, property_version }
+            } else {
+                JobOperation::OfferToken { receiver, creator, collection, name, amount, property_version }
+            };
+            operations.push(operation);
+        }
+        Ok(operations)
+    }
+
+    /// Runs the airdrop journal, `options.concurrency` transactions at a
+    /// time, then reports on every recipient. `options.receivers` holds the
+    /// co-signers planned for direct transfers.
+    pub async fn run_airdrop(
+        &self,
+        journal: &mut Journal,
+        sender: &mut dyn TransactionSigner,
+        options: JournalRunOptions<'_>,
+    ) -> Result<AirdropReport> {
+        self.run_journal_with(journal, sender, options).await?;
+        self.airdrop_report(journal, sender.address()).await
+    }
+
+    /// Offers that are no longer pending are delivered or cancelled,
+    /// whichever of the sender's claim and cancel events came last.
+    pub async fn airdrop_report(&self, journal: &Journal, sender: AccountAddress) -> Result<AirdropReport> {
+        let closed_offers = self.list_closed_offers(sender).await?;
+        let mut report = AirdropReport::default();
+        for job in journal.jobs() {
+            let (recipient, method) = match delivery(&job.operation) {
+                Some(delivery) => delivery,
+                None => continue,
+            };
+            let mut outcome = AirdropOutcome {
+                recipient,
+                method,
+                hash: None,
+                reason: None,
+            };
+
+            match &job.state {
+                JobState::Succeeded { hash, version } => {
+                    outcome.hash = Some(hash.clone());
+                    if method != DeliveryMethod::Offer {
+                        report.delivered.push(outcome);
+                        continue;
+                    }
+                    let unclaimed = self
+                        .get_offer_amount(sender, outcome.recipient.address, outcome.recipient.token_id.clone())
+                        .await?
+                        > 0;
+                    let offer_id = TokenOfferId {
+                        to_addr: outcome.recipient.address,
+                        token_id: outcome.recipient.token_id.clone(),
+                    };
+                    let closing = closed_offers
+                        .get(&offer_id)
+                        .and_then(|closings| closings.iter().filter(|(at, _)| at > version).last())
+                        .map(|(_, closing)| *closing);
+                    match closing {
+                        _ if unclaimed => report.offered_unclaimed.push(outcome),
+                        Some(OfferClosing::Cancelled) => report.cancelled.push(outcome),
+                        Some(OfferClosing::Claimed) | None => report.delivered.push(outcome),
+                    }
+                }
+                JobState::Failed { hash, vm_status } => {
+                    outcome.hash = Some(hash.clone());
+                    outcome.reason = Some(vm_status.clone());
+                    report.failed.push(outcome);
+                }
+                _ => report.pending.push(outcome),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Once `deadline_secs` (a Unix timestamp) has passed, cancels every
+    /// offer of the airdrop still unclaimed, tracked in its own journal at
+    /// `path` so the cancellation can be resumed too.
+    pub async fn cancel_unclaimed_offers(
+        &self,
+        airdrop: &Journal,
+        sender: &mut dyn TransactionSigner,
+        path: impl AsRef<Path>,
+        deadline_secs: u64,
+        options: Option<TransactionOptions>,
+    ) -> Result<JournalSummary> {
+        let mut journal = if path.as_ref().exists() {
+            Journal::open(path)?
+        } else {
+            let operations = self
+                .plan_cancel_unclaimed_offers(airdrop, sender.address(), deadline_secs)
+                .await?;
+            Journal::create(path, operations)?
+        };
+
+        self.run_journal(&mut journal, sender, options).await
+    }
+
+    /// The cancellations `cancel_unclaimed_offers` would run, without
+    /// writing a journal.
+    pub async fn plan_cancel_unclaimed_offers(
+        &self,
+        airdrop: &Journal,
+        sender: AccountAddress,
+        deadline_secs: u64,
+    ) -> Result<Vec<JobOperation>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        if now < deadline_secs {
+            bail!("The claim deadline has not passed yet, {} seconds left", deadline_secs - now);
+        }
+
+        let report = self.airdrop_report(airdrop, sender).await?;
+        Ok(report
+            .offered_unclaimed
+            .into_iter()
+            .map(|outcome| {
+                let token_data_id = outcome.recipient.token_id.token_data_id;
+                JobOperation::CancelTokenOffer {
+                    receiver: outcome.recipient.address,
+                    creator: token_data_id.creator,
+                    collection: token_data_id.collection,
+                    name: token_data_id.name,
+                    property_version: outcome.recipient.token_id.property_version.0,
+                }
+            })
+            .collect())
+    }
+}
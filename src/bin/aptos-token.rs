@@ -0,0 +1,665 @@
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{
+    bcs,
+    crypto::{ed25519::Ed25519PublicKey, PrivateKey},
+    rest_client::{aptos_api_types::U64, Client as ApiClient},
+    types::{account_address::AccountAddress, transaction::TransactionPayload},
+};
+use clap::{Args, Parser, Subcommand};
+use rust_aptos_token_client::{
+    accounts::{parse_address, parse_private_key, CliConfig, CliProfile},
+    airdrop::{check_recipients, load_recipients},
+    explain::explain_signed_transaction,
+    journal::{Journal, JournalRunOptions},
+    network::Network,
+    offline::{payload_function_name, signed_transaction_from_hex},
+    operation::TokenOperation,
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    types::{CollectionOptions, RoyaltyPoints, TokenDataId, TokenId, TokenMutability, TokenProperty, TransactionOptions},
+    TokenClient,
+};
+use serde_json::{json, Value};
+use url::Url;
+
+/// Everyday operations on the Aptos token standard. Every command prints
+/// JSON.
+#[derive(Parser)]
+#[clap(name = "aptos-token", version)]
+struct Cli {
+    /// Aptos CLI config holding the account profiles.
+    #[clap(long, global = true, default_value = ".aptos/config.yaml")]
+    config: PathBuf,
+
+    #[clap(long, global = true, default_value = "default")]
+    profile: String,
+
+    /// Node URL, overriding the profile's and the network's.
+    #[clap(long, global = true)]
+    url: Option<Url>,
+
+    /// mainnet, testnet, devnet or local.
+    #[clap(long, global = true)]
+    network: Option<Network>,
+
+    /// Run writes against the node without committing them.
+    #[clap(long, global = true)]
+    simulate: bool,
+
+    /// Print the payload of a write without signing or sending it.
+    #[clap(long, global = true)]
+    dry_run: bool,
+
+    /// Skip the confirmation prompt for writes on mainnet.
+    #[clap(long, global = true)]
+    yes: bool,
+
+    #[clap(long, global = true)]
+    max_gas_amount: Option<u64>,
+
+    #[clap(long, global = true)]
+    gas_unit_price: Option<u64>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[clap(subcommand)]
+    Collection(CollectionCommand),
+    #[clap(subcommand)]
+    Token(TokenCommand),
+    /// Offer tokens to a receiver.
+    Offer {
+        #[clap(long, parse(try_from_str = parse_address))]
+        receiver: AccountAddress,
+        #[clap(flatten)]
+        token: TokenArgs,
+        #[clap(long, default_value = "1")]
+        amount: u64,
+    },
+    /// Claim tokens offered by a sender.
+    Claim {
+        #[clap(long, parse(try_from_str = parse_address))]
+        sender: AccountAddress,
+        #[clap(flatten)]
+        token: TokenArgs,
+    },
+    /// Cancel an offer made to a receiver.
+    Cancel {
+        #[clap(long, parse(try_from_str = parse_address))]
+        receiver: AccountAddress,
+        #[clap(flatten)]
+        token: TokenArgs,
+    },
+    #[clap(subcommand)]
+    Swap(SwapCommand),
+    #[clap(subcommand)]
+    Airdrop(AirdropCommand),
+    /// Offers made by an account, and to it by the given senders.
+    PendingOffers {
+        /// Defaults to the profile's account.
+        #[clap(long, parse(try_from_str = parse_address))]
+        account: Option<AccountAddress>,
+        #[clap(long = "sender", parse(try_from_str = parse_address))]
+        senders: Vec<AccountAddress>,
+    },
+    /// Describe a committed transaction, or a signed one given as BCS hex.
+    Explain {
+        #[clap(long, required_unless_present = "signed")]
+        hash: Option<String>,
+        #[clap(long, conflicts_with = "hash")]
+        signed: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CollectionCommand {
+    Create {
+        #[clap(long)]
+        name: String,
+        #[clap(long, default_value = "")]
+        description: String,
+        #[clap(long, default_value = "")]
+        uri: String,
+        /// 0 for unlimited.
+        #[clap(long, default_value = "0")]
+        maximum: u64,
+        #[clap(long)]
+        description_mutable: bool,
+        #[clap(long)]
+        uri_mutable: bool,
+        #[clap(long)]
+        supply_mutable: bool,
+    },
+    Show {
+        #[clap(long, parse(try_from_str = parse_address))]
+        creator: AccountAddress,
+        #[clap(long)]
+        name: String,
+    },
+    List {
+        #[clap(long, parse(try_from_str = parse_address))]
+        creator: AccountAddress,
+        #[clap(long)]
+        cursor: Option<u64>,
+        #[clap(long, default_value = "25")]
+        limit: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommand {
+    Create {
+        #[clap(long)]
+        collection: String,
+        #[clap(long)]
+        name: String,
+        #[clap(long, default_value = "")]
+        description: String,
+        #[clap(long, default_value = "")]
+        uri: String,
+        #[clap(long, default_value = "1")]
+        supply: u64,
+        #[clap(long, default_value = "1")]
+        maximum: u64,
+        #[clap(long, parse(try_from_str = parse_address))]
+        royalty_payee: Option<AccountAddress>,
+        #[clap(long, default_value = "0")]
+        royalty_numerator: u64,
+        #[clap(long, default_value = "0")]
+        royalty_denominator: u64,
+        #[clap(long)]
+        maximum_mutable: bool,
+        #[clap(long)]
+        uri_mutable: bool,
+        #[clap(long)]
+        royalty_mutable: bool,
+        #[clap(long)]
+        description_mutable: bool,
+        #[clap(long)]
+        properties_mutable: bool,
+        /// `key:type=value`, repeatable.
+        #[clap(long = "property", parse(try_from_str = parse_property))]
+        properties: Vec<(String, String, String)>,
+    },
+    /// Mint more of a token created by the profile's account.
+    Mint {
+        #[clap(long)]
+        collection: String,
+        #[clap(long)]
+        name: String,
+        #[clap(long, default_value = "1")]
+        amount: u64,
+    },
+    Show {
+        #[clap(flatten)]
+        token: TokenArgs,
+        /// Also show this account's balance of the token.
+        #[clap(long, parse(try_from_str = parse_address))]
+        owner: Option<AccountAddress>,
+    },
+    Burn {
+        #[clap(flatten)]
+        token: TokenArgs,
+        #[clap(long, default_value = "1")]
+        amount: u64,
+    },
+    /// Set properties on tokens held by `owner`, as their creator.
+    Mutate {
+        #[clap(long, parse(try_from_str = parse_address))]
+        owner: AccountAddress,
+        #[clap(long)]
+        collection: String,
+        #[clap(long)]
+        name: String,
+        #[clap(long, default_value = "0")]
+        property_version: u64,
+        #[clap(long, default_value = "1")]
+        amount: u64,
+        /// `key:type=value`, repeatable.
+        #[clap(long = "property", parse(try_from_str = parse_property), required = true)]
+        properties: Vec<(String, String, String)>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SwapCommand {
+    /// List tokens for sale against a coin.
+    List {
+        #[clap(flatten)]
+        token: TokenArgs,
+        #[clap(long, default_value = "1")]
+        amount: u64,
+        #[clap(long)]
+        min_coin_per_token: u64,
+        #[clap(long, default_value = "0")]
+        locked_until_secs: u64,
+        #[clap(long, default_value = "0x1::aptos_coin::AptosCoin")]
+        coin_type: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AirdropCommand {
+    /// Deliver tokens to a JSON list of recipients, or resume a journal.
+    Run {
+        #[clap(long)]
+        recipients: PathBuf,
+        /// Progress file; rerun with the same path to resume.
+        #[clap(long)]
+        journal: PathBuf,
+        #[clap(long, default_value = "4")]
+        concurrency: usize,
+    },
+    /// Cancel offers of an airdrop still unclaimed after a deadline.
+    CancelUnclaimed {
+        #[clap(long)]
+        journal: PathBuf,
+        /// Progress file of the cancellation.
+        #[clap(long)]
+        cancel_journal: PathBuf,
+        /// Unix timestamp.
+        #[clap(long)]
+        deadline: u64,
+    },
+}
+
+#[derive(Args)]
+struct TokenArgs {
+    #[clap(long, parse(try_from_str = parse_address))]
+    creator: AccountAddress,
+    #[clap(long)]
+    collection: String,
+    #[clap(long)]
+    name: String,
+    #[clap(long, default_value = "0")]
+    property_version: u64,
+}
+
+fn parse_property(property: &str) -> Result<(String, String, String)> {
+    let (key_type, value) = property
+        .split_once('=')
+        .with_context(|| format!("Property `{}` is not `key:type=value`", property))?;
+    let (key, type_) = key_type
+        .split_once(':')
+        .with_context(|| format!("Property `{}` is not `key:type=value`", property))?;
+    Ok((key.to_string(), type_.to_string(), value.to_string()))
+}
+
+fn token_property(properties: Vec<(String, String, String)>) -> TokenProperty {
+    let mut token_property = TokenProperty::default();
+    for (key, type_, value) in properties {
+        token_property.keys.push(key);
+        token_property.types.push(type_);
+        token_property.values.push(value);
+    }
+    token_property
+}
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn confirm_on_terminal(function_name: &str) -> Result<()> {
+    eprint!("Submit {} on mainnet? [y/N] ", function_name);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if answer.trim().eq_ignore_ascii_case("y") {
+        Ok(())
+    } else {
+        bail!("Aborted")
+    }
+}
+
+struct Session {
+    cli: Cli,
+    profile: Option<CliProfile>,
+}
+
+impl Session {
+    fn new(cli: Cli) -> Result<Self> {
+        let profile = if cli.config.exists() {
+            Some(CliConfig::load(&cli.config)?.profile(&cli.profile)?.clone())
+        } else {
+            None
+        };
+        Ok(Self { cli, profile })
+    }
+
+    fn profile(&self) -> Result<&CliProfile> {
+        self.profile
+            .as_ref()
+            .with_context(|| format!("No Aptos CLI config at {}", self.cli.config.display()))
+    }
+
+    fn node_url(&self) -> Result<Url> {
+        if let Some(url) = &self.cli.url {
+            return Ok(url.clone());
+        }
+        if let Some(url) = self.profile.as_ref().and_then(|profile| profile.rest_url.clone()) {
+            return Ok(url);
+        }
+        match &self.cli.network {
+            Some(network) => Ok(network.node_url()),
+            None => bail!("No node URL: pass --url, --network or use a profile with a rest_url"),
+        }
+    }
+
+    fn public_key(&self) -> Result<Ed25519PublicKey> {
+        let private_key = self.profile()?.private_key.as_deref().context("Profile has no private key")?;
+        Ok(parse_private_key(private_key)?.public_key())
+    }
+
+    fn sender(&self) -> Result<AccountAddress> {
+        match self.profile()?.address()? {
+            Some(address) => Ok(address),
+            None => bail!("Profile has no account address"),
+        }
+    }
+
+    fn options(&self) -> TransactionOptions {
+        let mut options = TransactionOptions::default();
+        if let Some(max_gas_amount) = self.cli.max_gas_amount {
+            options.max_gas_amount = max_gas_amount;
+        }
+        if let Some(gas_unit_price) = self.cli.gas_unit_price {
+            options.gas_unit_price = gas_unit_price;
+        }
+        options
+    }
+
+    /// Prints, simulates, or signs and submits `payload` depending on the
+    /// global flags, then prints the outcome.
+    async fn write(&self, api_client: &ApiClient, client: &TokenClient<'_>, payload: TransactionPayload) -> Result<()> {
+        let sender = self.sender()?;
+        if self.cli.dry_run {
+            return print_json(&json!({
+                "sender": sender.to_hex_literal(),
+                "function": payload_function_name(&payload),
+                "summary": TokenOperation::from_payload(&payload, &[])?.to_string(),
+                "payload": hex::encode(bcs::to_bytes(&payload)?),
+            }));
+        }
+        if self.cli.simulate {
+            let result = client
+                .simulate_payload(sender, &self.public_key()?, payload, Some(self.options()))
+                .await?;
+            return print_json(&result);
+        }
+
+        let mut account = self.profile()?.load_account(api_client).await?;
+        let pending = client.submit_payload(&mut account, payload, Some(self.options())).await?;
+        let transaction = api_client
+            .wait_for_transaction(&pending)
+            .await
+            .context("Failed on waiting for transaction")?
+            .into_inner();
+        print_json(&serde_json::to_value(transaction)?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let session = Session::new(Cli::parse())?;
+    let api_client = ApiClient::new(session.node_url()?);
+    let mut client = TokenClient::new(&api_client).await?;
+    if let Some(network) = &session.cli.network {
+        network.check_chain_id(client.chain_id())?;
+    }
+    if !session.cli.yes {
+        client = client.with_mainnet_confirmation(confirm_on_terminal);
+    }
+
+    match &session.cli.command {
+        Command::Collection(CollectionCommand::Create {
+            name,
+            description,
+            uri,
+            maximum,
+            description_mutable,
+            uri_mutable,
+            supply_mutable,
+        }) => {
+            let request = CreateCollectionRequest::builder(name.clone())
+                .description(description.clone())
+                .uri(uri.clone())
+                .maximum(*maximum)
+                .mutability(CollectionOptions {
+                    description_mutable: *description_mutable,
+                    uri_mutable: *uri_mutable,
+                    supply_mutable: *supply_mutable,
+                })
+                .build()?;
+            session.write(&api_client, &client, client.create_collection_payload(&request)?).await
+        }
+        Command::Collection(CollectionCommand::Show { creator, name }) => {
+            let collection = client.get_collection_data(*creator, name.clone()).await?;
+            print_json(&serde_json::to_value(collection)?)
+        }
+        Command::Collection(CollectionCommand::List { creator, cursor, limit }) => {
+            let page = client.list_collections(*creator, *cursor, *limit).await?;
+            print_json(&json!({ "items": page.items, "next_cursor": page.next_cursor }))
+        }
+        Command::Token(TokenCommand::Create {
+            collection,
+            name,
+            description,
+            uri,
+            supply,
+            maximum,
+            royalty_payee,
+            royalty_numerator,
+            royalty_denominator,
+            maximum_mutable,
+            uri_mutable,
+            royalty_mutable,
+            description_mutable,
+            properties_mutable,
+            properties,
+        }) => {
+            let sender = session.sender()?;
+            let mut builder = CreateTokenRequest::builder(collection.clone(), name.clone())
+                .description(description.clone())
+                .uri(uri.clone())
+                .supply(*supply)
+                .maximum(*maximum)
+                .royalty(
+                    royalty_payee.unwrap_or(sender),
+                    RoyaltyPoints {
+                        denominator: *royalty_denominator,
+                        numerator: *royalty_numerator,
+                    },
+                )
+                .mutability(TokenMutability {
+                    maximum: *maximum_mutable,
+                    uri: *uri_mutable,
+                    royalty: *royalty_mutable,
+                    description: *description_mutable,
+                    properties: *properties_mutable,
+                });
+            for (key, type_, value) in properties {
+                builder = builder.property(key.clone(), value.clone(), type_.clone());
+            }
+            let request = builder.build()?;
+            session.write(&api_client, &client, client.create_token_payload(sender, &request)?).await
+        }
+        Command::Token(TokenCommand::Mint { collection, name, amount }) => {
+            let payload = client.mint_token_payload(session.sender()?, collection, name, *amount)?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Token(TokenCommand::Show { token, owner }) => {
+            let token_data = client
+                .get_token_data(token.creator, token.collection.clone(), token.name.clone())
+                .await?;
+            let balance = match owner {
+                Some(owner) => {
+                    let token_id = TokenId {
+                        token_data_id: TokenDataId {
+                            creator: token.creator,
+                            collection: token.collection.clone(),
+                            name: token.name.clone(),
+                        },
+                        property_version: U64(token.property_version),
+                    };
+                    Some(client.get_token_for_account(*owner, token_id).await?.amount.0)
+                }
+                None => None,
+            };
+            print_json(&json!({ "token_data": token_data, "balance": balance }))
+        }
+        Command::Token(TokenCommand::Burn { token, amount }) => {
+            let payload = client.burn_token_payload(
+                token.creator,
+                &token.collection,
+                &token.name,
+                *amount,
+                token.property_version,
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Token(TokenCommand::Mutate {
+            owner,
+            collection,
+            name,
+            property_version,
+            amount,
+            properties,
+        }) => {
+            let payload = client.mutate_token_properties_payload(
+                *owner,
+                session.sender()?,
+                collection,
+                name,
+                *property_version,
+                *amount,
+                &token_property(properties.clone()),
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Offer { receiver, token, amount } => {
+            let payload = client.offer_token_payload(
+                *receiver,
+                token.creator,
+                &token.collection,
+                &token.name,
+                *amount,
+                token.property_version,
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Claim { sender, token } => {
+            let payload = client.claim_token_payload(
+                *sender,
+                token.creator,
+                &token.collection,
+                &token.name,
+                token.property_version,
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Cancel { receiver, token } => {
+            let payload = client.cancel_token_offer_payload(
+                *receiver,
+                token.creator,
+                &token.collection,
+                &token.name,
+                token.property_version,
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Swap(SwapCommand::List {
+            token,
+            amount,
+            min_coin_per_token,
+            locked_until_secs,
+            coin_type,
+        }) => {
+            let payload = client.list_token_for_swap_payload(
+                token.creator,
+                &token.collection,
+                &token.name,
+                *amount,
+                *min_coin_per_token,
+                *locked_until_secs,
+                token.property_version,
+                coin_type,
+            )?;
+            session.write(&api_client, &client, payload).await
+        }
+        Command::Airdrop(AirdropCommand::Run { recipients, journal, concurrency }) => {
+            if session.cli.simulate {
+                bail!("--simulate is not supported for airdrops; use --dry-run to print the planned jobs");
+            }
+            if session.cli.dry_run {
+                let recipients = load_recipients(recipients)?;
+                if journal.exists() {
+                    let journal = Journal::open(journal)?;
+                    check_recipients(&journal, &recipients)?;
+                    return print_json(&serde_json::to_value(journal.jobs())?);
+                }
+                let operations = client.plan_airdrop_operations(&recipients, &HashSet::new()).await?;
+                return print_json(&serde_json::to_value(operations)?);
+            }
+            let recipients = load_recipients(recipients)?;
+            let mut journal = client.plan_airdrop(journal, &recipients, &HashSet::new()).await?;
+            let mut account = session.profile()?.load_account(&api_client).await?;
+            let options = JournalRunOptions {
+                transaction_options: Some(session.options()),
+                concurrency: *concurrency,
+                ..Default::default()
+            };
+            let report = client.run_airdrop(&mut journal, &mut account, options).await?;
+            print_json(&serde_json::to_value(report)?)
+        }
+        Command::Airdrop(AirdropCommand::CancelUnclaimed { journal, cancel_journal, deadline }) => {
+            if session.cli.simulate {
+                bail!("--simulate is not supported for airdrops; use --dry-run to print the planned cancellations");
+            }
+            let journal = Journal::open(journal)?;
+            if session.cli.dry_run {
+                if cancel_journal.exists() {
+                    return print_json(&serde_json::to_value(Journal::open(cancel_journal)?.jobs())?);
+                }
+                let operations = client
+                    .plan_cancel_unclaimed_offers(&journal, session.sender()?, *deadline)
+                    .await?;
+                return print_json(&serde_json::to_value(operations)?);
+            }
+            let mut account = session.profile()?.load_account(&api_client).await?;
+            let summary = client
+                .cancel_unclaimed_offers(&journal, &mut account, cancel_journal, *deadline, Some(session.options()))
+                .await?;
+            print_json(&json!({
+                "cancelled": summary.succeeded,
+                "failed": summary.failed,
+                "remaining": summary.remaining,
+            }))
+        }
+        Command::PendingOffers { account, senders } => {
+            let account = match account {
+                Some(account) => *account,
+                None => session.sender()?,
+            };
+            let outgoing = client.list_outgoing_offers(account).await?;
+            let incoming = client.list_incoming_offers(account, senders).await?;
+            print_json(&json!({ "outgoing": outgoing, "incoming": incoming }))
+        }
+        Command::Explain { hash, signed } => {
+            let explanation = match (hash, signed) {
+                (Some(hash), _) => client.explain_transaction_by_hash(hash).await?,
+                (None, Some(signed)) => explain_signed_transaction(&signed_transaction_from_hex(signed)?)?,
+                (None, None) => bail!("Pass --hash or --signed"),
+            };
+            print_json(&serde_json::to_value(explanation)?)
+        }
+    }
+}
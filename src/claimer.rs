@@ -0,0 +1,311 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use aptos_sdk::types::account_address::AccountAddress;
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    signer::TransactionSigner,
+    types::{PendingOffer, TokenId, TransactionOptions},
+    TokenClient,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AllowedCollection {
+    pub creator: AccountAddress,
+    pub collection: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AllowedSender {
+    pub sender: AccountAddress,
+    /// Collections whose tokens are claimed from this sender. Empty allows
+    /// every collection.
+    #[serde(default)]
+    pub collections: Vec<AllowedCollection>,
+}
+
+/// Which offers the auto-claimer accepts. Offers from senders not listed
+/// are never read, since pending claims are stored by sender.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ClaimPolicy {
+    pub senders: Vec<AllowedSender>,
+}
+
+impl ClaimPolicy {
+    /// Reads a YAML policy from `.yaml`/`.yml` files, JSON otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content).context("Error on parsing claim policy"),
+            _ => serde_json::from_str(&content).context("Error on parsing claim policy"),
+        }
+    }
+
+    pub fn allows(&self, offer: &PendingOffer) -> bool {
+        let token_data_id = &offer.token.id.token_data_id;
+        self.senders.iter().any(|rule| {
+            rule.sender == offer.sender
+                && (rule.collections.is_empty()
+                    || rule.collections.iter().any(|allowed| {
+                        allowed.creator == token_data_id.creator && allowed.collection == token_data_id.collection
+                    }))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ClaimOutcome {
+    Claimed { hash: String },
+    Failed { reason: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClaimAuditRecord {
+    pub timestamp_secs: u64,
+    pub sender: AccountAddress,
+    pub receiver: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: u64,
+    #[serde(flatten)]
+    pub outcome: ClaimOutcome,
+}
+
+impl ClaimAuditRecord {
+    /// Appends the record to a JSON lines file.
+    pub fn append_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+pub struct AutoClaimOptions {
+    /// Wait between two passes over the allowed senders.
+    pub poll_interval: Duration,
+    /// JSON lines file every audit record is appended to.
+    pub audit_log: Option<PathBuf>,
+    /// Failed claims of one offer before the claimer gives up on it.
+    pub max_attempts: u32,
+    pub transaction_options: Option<TransactionOptions>,
+}
+
+impl Default for AutoClaimOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(10),
+            audit_log: None,
+            max_attempts: 3,
+            transaction_options: None,
+        }
+    }
+}
+
+struct ClaimerState {
+    accounts: HashMap<AccountAddress, Box<dyn TransactionSigner>>,
+    policy: ClaimPolicy,
+    options: AutoClaimOptions,
+    /// Offer, claim and cancel counters of each sender at its last
+    /// successful pass.
+    counters: HashMap<AccountAddress, (u64, u64, u64)>,
+    /// Failed claims so far, by sender, receiver and token.
+    attempts: HashMap<(AccountAddress, AccountAddress, TokenId), u32>,
+    buffer: VecDeque<Result<ClaimAuditRecord>>,
+    wait: bool,
+}
+
+impl<'a> TokenClient<'a> {
+    async fn claim_offer(
+        &self,
+        account: &mut dyn TransactionSigner,
+        offer: &PendingOffer,
+        options: Option<TransactionOptions>,
+    ) -> Result<String> {
+        let token_id = &offer.token.id;
+        let pending = self
+            .claim_token(
+                account,
+                offer.sender,
+                token_id.token_data_id.creator,
+                token_id.token_data_id.collection.clone(),
+                token_id.token_data_id.name.clone(),
+                Some(token_id.property_version.0),
+                options,
+            )
+            .await?;
+        self.api_client
+            .wait_for_transaction(&pending)
+            .await
+            .context("Claim transaction failed")?;
+        Ok(pending.hash.to_string())
+    }
+
+    async fn resync_sequence_number(&self, account: &mut dyn TransactionSigner) -> Result<()> {
+        let sequence_number = self
+            .api_client
+            .get_account(account.address())
+            .await
+            .with_context(|| format!("Failed to get account {}", account.address().to_hex_literal()))?
+            .into_inner()
+            .sequence_number;
+        account.set_sequence_number(sequence_number);
+        Ok(())
+    }
+
+    /// Claims every allowed offer to a managed account, skipping senders
+    /// whose pending claims have not changed since their last pass. A
+    /// sender is read again after a failed claim, until the offer has
+    /// failed `max_attempts` times. Errors reading a sender or writing the
+    /// audit log are buffered and the pass moves on.
+    async fn claim_pass(&self, state: &mut ClaimerState) -> Result<()> {
+        for rule in state.policy.senders.clone() {
+            let sender = rule.sender;
+            let resources = match self.try_get_pending_claims_resources_for_account(sender).await {
+                Ok(Some(resources)) => resources,
+                // The sender never made an offer.
+                Ok(None) => continue,
+                Err(e) => {
+                    state
+                        .buffer
+                        .push_back(Err(e.context(format!("Failed to read offers from {}", sender.to_hex_literal()))));
+                    continue;
+                }
+            };
+            let counters = (
+                resources.offer_events.counter.0,
+                resources.claim_events.counter.0,
+                resources.cancel_offer_events.counter.0,
+            );
+            if state.counters.get(&sender) == Some(&counters) {
+                continue;
+            }
+
+            let offers = match self.list_outgoing_offers(sender).await {
+                Ok(offers) => offers,
+                Err(e) => {
+                    state
+                        .buffer
+                        .push_back(Err(e.context(format!("Failed to read offers from {}", sender.to_hex_literal()))));
+                    continue;
+                }
+            };
+
+            let mut all_claimed = true;
+            for offer in offers {
+                if !state.policy.allows(&offer) {
+                    continue;
+                }
+                let account = match state.accounts.get_mut(&offer.receiver) {
+                    Some(account) => account,
+                    None => continue,
+                };
+                let key = (sender, offer.receiver, offer.token.id.clone());
+                let attempts = state.attempts.get(&key).copied().unwrap_or(0);
+                if attempts >= state.options.max_attempts {
+                    continue;
+                }
+
+                let outcome = match self
+                    .claim_offer(account.as_mut(), &offer, state.options.transaction_options.clone())
+                    .await
+                {
+                    Ok(hash) => {
+                        state.attempts.remove(&key);
+                        ClaimOutcome::Claimed { hash }
+                    }
+                    Err(e) => {
+                        // A rejected submission leaves the signer's counter
+                        // ahead of the chain, stalling every later claim.
+                        if let Err(e) = self.resync_sequence_number(account.as_mut()).await {
+                            state.buffer.push_back(Err(e));
+                        }
+                        let attempts = attempts + 1;
+                        state.attempts.insert(key, attempts);
+                        if attempts < state.options.max_attempts {
+                            all_claimed = false;
+                            ClaimOutcome::Failed { reason: format!("{:#}", e) }
+                        } else {
+                            ClaimOutcome::Failed {
+                                reason: format!("{:#}; giving up after {} attempts", e, attempts),
+                            }
+                        }
+                    }
+                };
+                let record = ClaimAuditRecord {
+                    timestamp_secs: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+                    sender,
+                    receiver: offer.receiver,
+                    token_id: offer.token.id.clone(),
+                    amount: offer.token.amount.0,
+                    outcome,
+                };
+                if let Some(audit_log) = &state.options.audit_log {
+                    if let Err(e) = record.append_to(audit_log) {
+                        state.buffer.push_back(Err(e));
+                    }
+                }
+                state.buffer.push_back(Ok(record));
+            }
+
+            // Our own claims bump the claim counter, so the next pass reads
+            // the sender once more before settling.
+            if all_claimed {
+                state.counters.insert(sender, counters);
+            } else {
+                state.counters.remove(&sender);
+            }
+        }
+        Ok(())
+    }
+
+    /// Watches offers from the senders of `policy` to any of `accounts`
+    /// and claims the allowed ones, forever. Yields an audit record per
+    /// claim attempt; errors are yielded too and the claimer keeps going.
+    pub fn auto_claim(
+        &self,
+        accounts: Vec<Box<dyn TransactionSigner>>,
+        policy: ClaimPolicy,
+        options: AutoClaimOptions,
+    ) -> impl Stream<Item = Result<ClaimAuditRecord>> + '_ {
+        let state = ClaimerState {
+            accounts: accounts.into_iter().map(|account| (account.address(), account)).collect(),
+            policy,
+            options,
+            counters: HashMap::new(),
+            attempts: HashMap::new(),
+            buffer: VecDeque::new(),
+            wait: false,
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(record) = state.buffer.pop_front() {
+                    return Some((record, state));
+                }
+                if state.wait {
+                    tokio::time::sleep(state.options.poll_interval).await;
+                }
+
+                state.wait = true;
+                if let Err(e) = self.claim_pass(&mut state).await {
+                    return Some((Err(e), state));
+                }
+            }
+        })
+    }
+}
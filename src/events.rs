@@ -0,0 +1,304 @@
+use std::{collections::VecDeque, time::Duration};
+
+use anyhow::{Context, Result};
+use aptos_sdk::{rest_client::Client as ApiClient, types::account_address::AccountAddress};
+use futures::{stream, Stream};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    types::{
+        BurnTokenEvent, CreateCollectionEvent, CreateTokenDataEvent, DepositEvent, MintTokenEvent,
+        MutateTokenPropertyMapEvent, TokenCancelOfferEvent, TokenClaimEvent, TokenOfferEvent,
+        WithdrawEvent,
+    },
+    TokenClient,
+};
+
+/// Largest page the fullnode returns for event queries.
+pub const EVENT_PAGE_SIZE: u16 = 100;
+
+#[derive(Debug, Clone)]
+pub struct TypedEvent<T> {
+    pub version: u64,
+    pub sequence_number: u64,
+    pub data: T,
+}
+
+/// Reads up to `limit` events of the handle `struct_tag.field_name` held
+/// by `address`, starting at sequence number `start`.
+pub async fn get_events<T: DeserializeOwned>(
+    api_client: &ApiClient,
+    address: AccountAddress,
+    struct_tag: &str,
+    field_name: &str,
+    start: u64,
+    limit: u16,
+) -> Result<Vec<TypedEvent<T>>> {
+    let events = api_client
+        .get_account_events(address, struct_tag, field_name, Some(start), Some(limit))
+        .await
+        .with_context(|| format!("Error on getting events <{}.{}>", struct_tag, field_name))?
+        .into_inner();
+
+    events
+        .into_iter()
+        .map(|event| {
+            Ok(TypedEvent {
+                version: event.version.0,
+                sequence_number: event.sequence_number.0,
+                data: serde_json::from_value(event.data)
+                    .with_context(|| format!("Error on parsing event <{}.{}>", struct_tag, field_name))?,
+            })
+        })
+        .collect()
+}
+
+/// Reads the first `count` events of a handle, `count` usually being the
+/// handle's counter.
+pub async fn get_all_events<T: DeserializeOwned>(
+    api_client: &ApiClient,
+    address: AccountAddress,
+    struct_tag: &str,
+    field_name: &str,
+    count: u64,
+) -> Result<Vec<TypedEvent<T>>> {
+    let mut events = Vec::new();
+    while (events.len() as u64) < count {
+        let page = get_events(
+            api_client,
+            address,
+            struct_tag,
+            field_name,
+            events.len() as u64,
+            EVENT_PAGE_SIZE,
+        )
+        .await?;
+        if page.is_empty() {
+            break;
+        }
+        events.extend(page);
+    }
+    Ok(events)
+}
+
+/// One page of a listing. Pass `next_cursor` back to continue; it is
+/// `None` once the underlying event handle is exhausted.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TokenEventHandle {
+    Deposit,
+    Withdraw,
+    Burn,
+    MutateTokenProperty,
+    Offer,
+    Claim,
+    CancelOffer,
+    CreateCollection,
+    CreateTokenData,
+    Mint,
+}
+
+impl TokenEventHandle {
+    pub fn struct_tag(&self) -> &'static str {
+        match self {
+            TokenEventHandle::Deposit
+            | TokenEventHandle::Withdraw
+            | TokenEventHandle::Burn
+            | TokenEventHandle::MutateTokenProperty => "0x3::token::TokenStore",
+            TokenEventHandle::Offer
+            | TokenEventHandle::Claim
+            | TokenEventHandle::CancelOffer => "0x3::token_transfers::PendingClaims",
+            TokenEventHandle::CreateCollection
+            | TokenEventHandle::CreateTokenData
+            | TokenEventHandle::Mint => "0x3::token::Collections",
+        }
+    }
+
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            TokenEventHandle::Deposit => "deposit_events",
+            TokenEventHandle::Withdraw => "withdraw_events",
+            TokenEventHandle::Burn => "burn_events",
+            TokenEventHandle::MutateTokenProperty => "mutate_token_property_events",
+            TokenEventHandle::Offer => "offer_events",
+            TokenEventHandle::Claim => "claim_events",
+            TokenEventHandle::CancelOffer => "cancel_offer_events",
+            TokenEventHandle::CreateCollection => "create_collection_events",
+            TokenEventHandle::CreateTokenData => "create_token_data_events",
+            TokenEventHandle::Mint => "mint_token_events",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenEvent {
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    Burn(BurnTokenEvent),
+    MutateTokenProperty(MutateTokenPropertyMapEvent),
+    Offer(TokenOfferEvent),
+    Claim(TokenClaimEvent),
+    CancelOffer(TokenCancelOfferEvent),
+    CreateCollection(CreateCollectionEvent),
+    CreateTokenData(CreateTokenDataEvent),
+    Mint(MintTokenEvent),
+}
+
+impl TokenEvent {
+    pub fn decode(handle: TokenEventHandle, data: Value) -> Result<Self> {
+        let event = match handle {
+            TokenEventHandle::Deposit => TokenEvent::Deposit(serde_json::from_value(data)?),
+            TokenEventHandle::Withdraw => TokenEvent::Withdraw(serde_json::from_value(data)?),
+            TokenEventHandle::Burn => TokenEvent::Burn(serde_json::from_value(data)?),
+            TokenEventHandle::MutateTokenProperty => {
+                TokenEvent::MutateTokenProperty(serde_json::from_value(data)?)
+            }
+            TokenEventHandle::Offer => TokenEvent::Offer(serde_json::from_value(data)?),
+            TokenEventHandle::Claim => TokenEvent::Claim(serde_json::from_value(data)?),
+            TokenEventHandle::CancelOffer => TokenEvent::CancelOffer(serde_json::from_value(data)?),
+            TokenEventHandle::CreateCollection => {
+                TokenEvent::CreateCollection(serde_json::from_value(data)?)
+            }
+            TokenEventHandle::CreateTokenData => {
+                TokenEvent::CreateTokenData(serde_json::from_value(data)?)
+            }
+            TokenEventHandle::Mint => TokenEvent::Mint(serde_json::from_value(data)?),
+        };
+        Ok(event)
+    }
+}
+
+/// Where a subscription resumes: the next event of `handle` on `account`
+/// to be read. Persist the cursor of the last processed event to resume
+/// after a restart.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+    pub account: AccountAddress,
+    pub handle: TokenEventHandle,
+    pub next_sequence_number: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventStreamOptions {
+    /// How long to wait before polling again once caught up, or after an
+    /// error.
+    pub poll_interval: Duration,
+    /// Events fetched per request. A new page is only requested once the
+    /// consumer has taken every event of the previous one.
+    pub page_size: u16,
+}
+
+impl Default for EventStreamOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            page_size: EVENT_PAGE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StreamedEvent {
+    pub event: TypedEvent<TokenEvent>,
+    /// Cursor pointing just past `event`.
+    pub cursor: EventCursor,
+}
+
+/// An event that fails to decode comes back as its error, so one bad
+/// event does not hide the rest of the page.
+pub async fn get_token_events(
+    api_client: &ApiClient,
+    cursor: &EventCursor,
+    limit: u16,
+) -> Result<Vec<TypedEvent<Result<TokenEvent>>>> {
+    let handle = cursor.handle;
+    Ok(get_events::<Value>(
+        api_client,
+        cursor.account,
+        handle.struct_tag(),
+        handle.field_name(),
+        cursor.next_sequence_number,
+        limit,
+    )
+    .await?
+    .into_iter()
+    .map(|event| TypedEvent {
+        version: event.version,
+        sequence_number: event.sequence_number,
+        data: TokenEvent::decode(handle, event.data).with_context(|| {
+            format!(
+                "Error on parsing event <{}.{}> number {}",
+                handle.struct_tag(),
+                handle.field_name(),
+                event.sequence_number
+            )
+        }),
+    })
+    .collect())
+}
+
+struct StreamState {
+    cursor: EventCursor,
+    options: EventStreamOptions,
+    buffer: VecDeque<TypedEvent<Result<TokenEvent>>>,
+    wait: bool,
+}
+
+impl<'a> TokenClient<'a> {
+    /// Polls the event handle in `cursor` forever, yielding decoded events
+    /// from `cursor.next_sequence_number` on. Errors are yielded too; the
+    /// stream keeps polling after them, and skips past events that fail to
+    /// decode.
+    pub fn event_stream(
+        &self,
+        cursor: EventCursor,
+        options: EventStreamOptions,
+    ) -> impl Stream<Item = Result<StreamedEvent>> + 'a {
+        let api_client = self.api_client;
+        let state = StreamState {
+            cursor,
+            options,
+            buffer: VecDeque::new(),
+            wait: false,
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(event) = state.buffer.pop_front() {
+                    state.cursor.next_sequence_number = event.sequence_number + 1;
+                    let data = match event.data {
+                        Ok(data) => data,
+                        Err(e) => return Some((Err(e), state)),
+                    };
+                    let event = TypedEvent {
+                        version: event.version,
+                        sequence_number: event.sequence_number,
+                        data,
+                    };
+                    let streamed = StreamedEvent { event, cursor: state.cursor.clone() };
+                    return Some((Ok(streamed), state));
+                }
+                if state.wait {
+                    tokio::time::sleep(state.options.poll_interval).await;
+                }
+
+                match get_token_events(api_client, &state.cursor, state.options.page_size).await {
+                    Ok(events) => {
+                        state.wait = events.is_empty();
+                        state.buffer.extend(events);
+                    }
+                    Err(e) => {
+                        state.wait = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
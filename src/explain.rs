@@ -0,0 +1,166 @@
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_sdk::types::{
+    account_address::AccountAddress,
+    transaction::{authenticator::TransactionAuthenticator, RawTransaction, SignedTransaction, TransactionPayload},
+};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{operation::TokenOperation, TokenClient};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommittedStatus {
+    pub hash: String,
+    pub version: u64,
+    pub success: bool,
+    pub vm_status: String,
+}
+
+/// What a transaction does, decoded from its payload.
+#[derive(Serialize, Clone, Debug)]
+pub struct TransactionExplanation {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub secondary_signers: Vec<AccountAddress>,
+    #[serde(flatten)]
+    pub operation: TokenOperation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub committed: Option<CommittedStatus>,
+    pub summary: String,
+}
+
+impl TransactionExplanation {
+    fn new(
+        sender: AccountAddress,
+        sequence_number: u64,
+        secondary_signers: Vec<AccountAddress>,
+        operation: TokenOperation,
+        committed: Option<CommittedStatus>,
+    ) -> Self {
+        let mut summary = format!(
+            "{}, sent by {} (sequence number {})",
+            operation,
+            sender.to_hex_literal(),
+            sequence_number
+        );
+        if let Some(committed) = &committed {
+            if committed.success {
+                summary.push_str(&format!(", committed at version {}", committed.version));
+            } else {
+                summary.push_str(&format!(
+                    ", failed at version {} with {}",
+                    committed.version, committed.vm_status
+                ));
+            }
+        }
+
+        Self {
+            sender,
+            sequence_number,
+            secondary_signers,
+            operation,
+            committed,
+            summary,
+        }
+    }
+}
+
+fn explain_payload(
+    sender: AccountAddress,
+    sequence_number: u64,
+    payload: &TransactionPayload,
+    secondary_signers: Vec<AccountAddress>,
+) -> Result<TransactionExplanation> {
+    let operation = TokenOperation::from_payload(payload, &secondary_signers)?;
+    Ok(TransactionExplanation::new(sender, sequence_number, secondary_signers, operation, None))
+}
+
+/// Secondary signers of a multi-agent transaction are not part of the raw
+/// transaction, so the receiver of a direct transfer is left unknown.
+pub fn explain_raw_transaction(raw_txn: &RawTransaction) -> Result<TransactionExplanation> {
+    explain_payload(raw_txn.sender(), raw_txn.sequence_number(), raw_txn.payload(), vec![])
+}
+
+pub fn explain_signed_transaction(signed_txn: &SignedTransaction) -> Result<TransactionExplanation> {
+    let secondary_signers = match signed_txn.authenticator() {
+        TransactionAuthenticator::MultiAgent { secondary_signer_addresses, .. } => secondary_signer_addresses,
+        _ => vec![],
+    };
+    explain_payload(signed_txn.sender(), signed_txn.sequence_number(), signed_txn.payload(), secondary_signers)
+}
+
+/// Explains a user transaction as returned by the REST API, e.g. from
+/// `transactions/by_hash`.
+pub fn explain_committed_transaction(transaction: &Value) -> Result<TransactionExplanation> {
+    if transaction["type"] != "user_transaction" {
+        bail!(
+            "Only user transactions can be explained, got {}",
+            transaction["type"].as_str().unwrap_or("unknown type")
+        );
+    }
+
+    let sender = transaction["sender"]
+        .as_str()
+        .and_then(|sender| AccountAddress::from_hex_literal(sender).ok())
+        .context("Transaction has no sender")?;
+    let sequence_number = transaction["sequence_number"]
+        .as_str()
+        .and_then(|sequence_number| sequence_number.parse().ok())
+        .context("Transaction has no sequence number")?;
+    let secondary_signers = match transaction["signature"]["secondary_signer_addresses"].as_array() {
+        Some(addresses) => addresses
+            .iter()
+            .map(|address| {
+                address
+                    .as_str()
+                    .and_then(|address| AccountAddress::from_hex_literal(address).ok())
+                    .context("Invalid secondary signer address")
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => vec![],
+    };
+    let committed = CommittedStatus {
+        hash: transaction["hash"].as_str().unwrap_or_default().to_string(),
+        version: transaction["version"]
+            .as_str()
+            .and_then(|version| version.parse().ok())
+            .context("Committed transaction has no version")?,
+        success: transaction["success"].as_bool().unwrap_or(false),
+        vm_status: transaction["vm_status"].as_str().unwrap_or_default().to_string(),
+    };
+
+    let operation = TokenOperation::from_json_payload(&transaction["payload"], &secondary_signers)?;
+    Ok(TransactionExplanation::new(
+        sender,
+        sequence_number,
+        secondary_signers,
+        operation,
+        Some(committed),
+    ))
+}
+
+impl<'a> TokenClient<'a> {
+    pub async fn explain_transaction_by_hash(&self, hash: &str) -> Result<TransactionExplanation> {
+        // Pushed as a path segment so that `hash` is percent-encoded.
+        let mut url = self
+            .api_client
+            .build_path("transactions/by_hash")
+            .context("Failed to build transaction URL")?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Failed to build transaction URL"))?
+            .push(hash);
+        let transaction: Value = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Error on getting transaction {}", hash))?
+            .error_for_status()
+            .with_context(|| format!("Error on getting transaction {}", hash))?
+            .json()
+            .await
+            .context("Error on parsing transaction")?;
+
+        explain_committed_transaction(&transaction)
+    }
+}
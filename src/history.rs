@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use aptos_sdk::{
+    rest_client::{aptos_api_types::U64, Client as ApiClient},
+    types::account_address::AccountAddress,
+};
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    types::{
+        CollectionData, CollectionsResources, Token, TokenData, TokenDataId, TokenDataStoreResources, TokenId,
+        TokenStoreResources,
+    },
+    TokenClient,
+};
+
+/// Table item at `ledger_version`, or at the latest version, `None` if the
+/// table has no such key.
+pub(crate) async fn try_get_table_item<K: Serialize>(
+    http_client: &reqwest::Client,
+    api_client: &ApiClient,
+    handle: AccountAddress,
+    key_type: &str,
+    value_type: &str,
+    key: K,
+    ledger_version: Option<u64>,
+) -> Result<Option<Value>> {
+    let path = match ledger_version {
+        Some(ledger_version) => format!("tables/{}/item?ledger_version={}", handle.to_hex_literal(), ledger_version),
+        None => format!("tables/{}/item", handle.to_hex_literal()),
+    };
+    let url = api_client
+        .build_path(&path)
+        .context("Failed to build table item URL")?;
+
+    let response = http_client
+        .post(url)
+        .json(&json!({
+            "key_type": key_type,
+            "value_type": value_type,
+            "key": key,
+        }))
+        .send()
+        .await
+        .context("Error on getting table item")?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let item = response
+        .error_for_status()
+        .with_context(|| format!("Error on getting table item <{}>", value_type))?
+        .json()
+        .await
+        .context("Error on parsing table item")?;
+    Ok(Some(item))
+}
+
+impl<'a> TokenClient<'a> {
+    /// The latest committed ledger version, to pin a series of
+    /// `_at_version` reads to the same state.
+    pub async fn get_ledger_version(&self) -> Result<u64> {
+        Ok(self
+            .api_client
+            .get_ledger_information()
+            .await
+            .context("Failed to get ledger information")?
+            .into_inner()
+            .version)
+    }
+
+    /// Data of the resource `resource_type` held by `account` at
+    /// `ledger_version`, `None` if it did not exist then.
+    pub(crate) async fn get_account_resource_at_version(
+        &self,
+        account: AccountAddress,
+        resource_type: &str,
+        ledger_version: u64,
+    ) -> Result<Option<Value>> {
+        let url = self
+            .api_client
+            .build_path(&format!(
+                "accounts/{}/resource/{}?ledger_version={}",
+                account.to_hex_literal(),
+                resource_type,
+                ledger_version
+            ))
+            .context("Failed to build resource URL")?;
+
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Error on getting account resource <{}>", resource_type))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let resource: Value = response
+            .error_for_status()
+            .with_context(|| format!("Error on getting account resource <{}>", resource_type))?
+            .json()
+            .await
+            .with_context(|| format!("Error on parsing account resource <{}>", resource_type))?;
+        Ok(Some(resource["data"].clone()))
+    }
+
+    /// `None` if the table had no such key at `ledger_version`.
+    pub(crate) async fn try_get_table_item_at_version<K: Serialize>(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+        ledger_version: u64,
+    ) -> Result<Option<Value>> {
+        try_get_table_item(
+            &self.http_client,
+            self.api_client,
+            handle,
+            key_type,
+            value_type,
+            key,
+            Some(ledger_version),
+        )
+        .await
+    }
+
+    /// `None` if the table has no such key. Other failures are errors, so
+    /// they are not mistaken for a missing item.
+    pub(crate) async fn try_get_table_item<K: Serialize>(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+    ) -> Result<Option<Value>> {
+        try_get_table_item(&self.http_client, self.api_client, handle, key_type, value_type, key, None).await
+    }
+
+    pub(crate) async fn get_table_item_at_version<K: Serialize>(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        value_type: &str,
+        key: K,
+        ledger_version: u64,
+    ) -> Result<Value> {
+        self.try_get_table_item_at_version(handle, key_type, value_type, key, ledger_version)
+            .await?
+            .with_context(|| format!("Table item <{}> not found at version {}", value_type, ledger_version))
+    }
+
+    pub async fn get_collection_data_at_version(
+        &self,
+        account: AccountAddress,
+        collection_name: String,
+        ledger_version: u64,
+    ) -> Result<CollectionData> {
+        let resource = self
+            .get_account_resource_at_version(account, "0x3::token::Collections", ledger_version)
+            .await?
+            .context("No NFT Collection Found")?;
+
+        let v: CollectionsResources = serde_json::from_value(resource)
+            .context("Error on parsing account resources")?;
+
+        let item = self
+            .get_table_item_at_version(
+                v.collection_data.handle,
+                "0x1::string::String",
+                "0x3::token::CollectionData",
+                collection_name,
+                ledger_version,
+            )
+            .await
+            .context("Target collection not found in provided account")?;
+
+        serde_json::from_value::<CollectionData>(item).context("Error on parsing collection data")
+    }
+
+    pub async fn get_token_at_version(
+        &self,
+        creator: AccountAddress,
+        collection_name: String,
+        token_name: String,
+        property_version: Option<u64>,
+        ledger_version: u64,
+    ) -> Result<Token> {
+        let token_id = TokenId {
+            token_data_id: TokenDataId {
+                creator,
+                collection: collection_name,
+                name: token_name,
+            },
+            property_version: U64(property_version.unwrap_or(0)),
+        };
+
+        self.get_token_for_account_at_version(creator, token_id, ledger_version).await
+    }
+
+    pub async fn get_token_for_account_at_version(
+        &self,
+        account: AccountAddress,
+        token_id: TokenId,
+        ledger_version: u64,
+    ) -> Result<Token> {
+        let resource = self
+            .get_account_resource_at_version(account, "0x3::token::TokenStore", ledger_version)
+            .await?
+            .context("No Token Found")?;
+
+        let data: TokenStoreResources = serde_json::from_value(resource)
+            .context("Error on parsing token store resources")?;
+
+        let item = self
+            .get_table_item_at_version(
+                data.tokens.handle,
+                "0x3::token::TokenId",
+                "0x3::token::Token",
+                token_id,
+                ledger_version,
+            )
+            .await
+            .context("Target Token ID not found in provided address")?;
+
+        serde_json::from_value::<Token>(item).context("Error on parsing token")
+    }
+
+    pub async fn get_token_data_at_version(
+        &self,
+        creator: AccountAddress,
+        collection_name: String,
+        token_name: String,
+        ledger_version: u64,
+    ) -> Result<TokenData> {
+        let resource = self
+            .get_account_resource_at_version(creator, "0x3::token::Collections", ledger_version)
+            .await?
+            .context("No NFT Collection found")?;
+
+        let data: TokenDataStoreResources = serde_json::from_value(resource)
+            .context("Error on parsing token data store resources")?;
+
+        let item = self
+            .get_table_item_at_version(
+                data.token_data.handle,
+                "0x3::token::TokenDataId",
+                "0x3::token::TokenData",
+                TokenDataId {
+                    creator,
+                    collection: collection_name,
+                    name: token_name,
+                },
+                ledger_version,
+            )
+            .await
+            .context("Target token not found")?;
+
+        serde_json::from_value::<TokenData>(item).context("Error on parsing token data")
+    }
+}
@@ -0,0 +1,633 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use aptos_sdk::{
+    rest_client::{aptos_api_types::U64, Client as ApiClient},
+    types::account_address::AccountAddress,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    accounts::parse_address,
+    offline::decode_hex,
+    types::{
+        BurnTokenEvent, CollectionData, CreateCollectionEvent, CreateTokenDataEvent, DepositEvent,
+        MintTokenEvent, TokenCancelOfferEvent, TokenClaimEvent, TokenData, TokenDataId, TokenId,
+        TokenOfferEvent, WithdrawEvent,
+    },
+};
+
+pub const DEFAULT_BATCH_SIZE: u16 = 100;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS collections (
+    creator TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    maximum INTEGER NOT NULL,
+    last_version INTEGER NOT NULL,
+    PRIMARY KEY (creator, name)
+);
+CREATE TABLE IF NOT EXISTS token_datas (
+    creator TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    uri TEXT NOT NULL,
+    maximum INTEGER NOT NULL,
+    supply INTEGER NOT NULL DEFAULT 0,
+    last_version INTEGER NOT NULL,
+    PRIMARY KEY (creator, collection, name)
+);
+CREATE TABLE IF NOT EXISTS ownerships (
+    owner TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    name TEXT NOT NULL,
+    property_version INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    last_version INTEGER NOT NULL,
+    PRIMARY KEY (owner, creator, collection, name, property_version)
+);
+CREATE INDEX IF NOT EXISTS ownerships_by_collection ON ownerships (creator, collection);
+CREATE TABLE IF NOT EXISTS pending_offers (
+    sender TEXT NOT NULL,
+    receiver TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    name TEXT NOT NULL,
+    property_version INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    last_version INTEGER NOT NULL,
+    PRIMARY KEY (sender, receiver, creator, collection, name, property_version)
+);
+CREATE TABLE IF NOT EXISTS swap_listings (
+    owner TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    collection TEXT NOT NULL,
+    name TEXT NOT NULL,
+    property_version INTEGER NOT NULL,
+    coin_type TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    min_price INTEGER NOT NULL,
+    locked_until_secs INTEGER NOT NULL,
+    last_version INTEGER NOT NULL,
+    PRIMARY KEY (owner, creator, collection, name, property_version, coin_type)
+);
+CREATE TABLE IF NOT EXISTS table_owners (
+    handle TEXT PRIMARY KEY,
+    owner TEXT NOT NULL,
+    resource_type TEXT NOT NULL
+);
+";
+
+const NEXT_VERSION_KEY: &str = "next_version";
+
+const LISTINGS_RESOURCE_PREFIX: &str = "0x3::token_coin_swap::TokenListings<";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TypeInfo {
+    pub account_address: AccountAddress,
+    pub module_name: String,
+    pub struct_name: String,
+}
+
+impl TypeInfo {
+    /// `module_name` and `struct_name` are hex encoded `vector<u8>`s.
+    pub fn to_type_name(&self) -> Result<String> {
+        Ok(format!(
+            "{}::{}::{}",
+            self.account_address.to_hex_literal(),
+            String::from_utf8(decode_hex(&self.module_name)?)?,
+            String::from_utf8(decode_hex(&self.struct_name)?)?,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenListingEvent {
+    pub token_id: TokenId,
+    pub amount: U64,
+    pub min_price: U64,
+    pub locked_until_secs: U64,
+    pub coin_type_info: TypeInfo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenSwapEvent {
+    pub token_id: TokenId,
+    pub token_buyer: AccountAddress,
+    pub token_amount: U64,
+    pub coin_amount: U64,
+    pub coin_type_info: TypeInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct Holding {
+    pub owner: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedOffer {
+    pub sender: AccountAddress,
+    pub receiver: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: u64,
+}
+
+/// Follows the fullnode's transaction stream and keeps `0x3::token`,
+/// `0x3::token_transfers` and `0x3::token_coin_swap` state in SQLite.
+/// Each batch is committed together with the next version to read, so a
+/// restarted indexer resumes where it stopped.
+pub struct Indexer<'a> {
+    api_client: &'a ApiClient,
+    conn: Connection,
+    batch_size: u16,
+}
+
+/// SQLite integers are signed, so values above `i64::MAX` are refused
+/// rather than wrapped.
+fn sql_int(value: u64, field: &str) -> Result<i64> {
+    i64::try_from(value).with_context(|| format!("{} {} does not fit in a SQLite integer", field, value))
+}
+
+fn from_sql_int(value: i64, field: &str) -> Result<u64> {
+    u64::try_from(value).with_context(|| format!("Negative {} {} in the indexer database", field, value))
+}
+
+fn token_id_params(token_id: &TokenId) -> Result<(String, &str, &str, i64)> {
+    Ok((
+        token_id.token_data_id.creator.to_hex_literal(),
+        &token_id.token_data_id.collection,
+        &token_id.token_data_id.name,
+        sql_int(token_id.property_version.0, "property version")?,
+    ))
+}
+
+fn token_id_from_row(creator: String, collection: String, name: String, property_version: i64) -> Result<TokenId> {
+    Ok(TokenId {
+        token_data_id: TokenDataId {
+            creator: parse_address(&creator)?,
+            collection,
+            name,
+        },
+        property_version: U64(from_sql_int(property_version, "property version")?),
+    })
+}
+
+impl<'a> Indexer<'a> {
+    pub fn open(api_client: &'a ApiClient, path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open indexer database")?;
+        conn.execute_batch(SCHEMA).context("Failed to create indexer tables")?;
+        Ok(Self { api_client, conn, batch_size: DEFAULT_BATCH_SIZE })
+    }
+
+    pub fn with_batch_size(mut self, batch_size: u16) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn next_version(&self) -> Result<u64> {
+        let version: Option<i64> = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", params![NEXT_VERSION_KEY], |row| row.get(0))
+            .optional()?;
+        from_sql_int(version.unwrap_or(0), "next version")
+    }
+
+    /// Indexes the next batch of transactions and returns how many were
+    /// read; 0 means the indexer has caught up.
+    pub async fn sync_batch(&mut self) -> Result<usize> {
+        let start = self.next_version()?;
+        let transactions = self
+            .api_client
+            .get_transactions(Some(start), Some(self.batch_size))
+            .await
+            .context("Failed to get transactions")?
+            .into_inner();
+        if transactions.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut next_version = start;
+        for transaction in &transactions {
+            if let Some(version) = apply_transaction(&tx, &serde_json::to_value(transaction)?)? {
+                next_version = version + 1;
+            }
+        }
+        tx.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![NEXT_VERSION_KEY, sql_int(next_version, "version")?],
+        )?;
+        tx.commit()?;
+
+        Ok(transactions.len())
+    }
+
+    /// Indexes one transaction as returned by the REST API, without moving
+    /// `next_version`.
+    pub fn apply_transaction(&mut self, transaction: &Value) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        apply_transaction(&tx, transaction)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Indexes forever, sleeping `poll_interval` whenever it catches up.
+    pub async fn run(&mut self, poll_interval: Duration) -> Result<()> {
+        loop {
+            if self.sync_batch().await? == 0 {
+                tokio::time::sleep(poll_interval).await;
+            }
+        }
+    }
+
+    pub fn collection_holders(&self, creator: AccountAddress, collection: &str) -> Result<Vec<Holding>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT owner, creator, collection, name, property_version, amount FROM ownerships
+             WHERE creator = ?1 AND collection = ?2 AND amount > 0
+             ORDER BY owner, name, property_version",
+        )?;
+        let rows = stmt.query_map(params![creator.to_hex_literal(), collection], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })?;
+
+        rows.map(|row| {
+            let (owner, creator, collection, name, property_version, amount) = row?;
+            Ok(Holding {
+                owner: parse_address(&owner)?,
+                token_id: token_id_from_row(creator, collection, name, property_version)?,
+                amount: from_sql_int(amount, "amount")?,
+            })
+        })
+        .collect()
+    }
+
+    pub fn tokens_owned(&self, owner: AccountAddress) -> Result<Vec<Holding>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT creator, collection, name, property_version, amount FROM ownerships
+             WHERE owner = ?1 AND amount > 0
+             ORDER BY creator, collection, name, property_version",
+        )?;
+        let rows = stmt.query_map(params![owner.to_hex_literal()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+
+        rows.map(|row| {
+            let (creator, collection, name, property_version, amount) = row?;
+            Ok(Holding {
+                owner,
+                token_id: token_id_from_row(creator, collection, name, property_version)?,
+                amount: from_sql_int(amount, "amount")?,
+            })
+        })
+        .collect()
+    }
+
+    pub fn pending_offers_to(&self, receiver: AccountAddress) -> Result<Vec<IndexedOffer>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT sender, creator, collection, name, property_version, amount FROM pending_offers
+             WHERE receiver = ?1 ORDER BY last_version",
+        )?;
+        let rows = stmt.query_map(params![receiver.to_hex_literal()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, i64>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })?;
+
+        rows.map(|row| {
+            let (sender, creator, collection, name, property_version, amount) = row?;
+            Ok(IndexedOffer {
+                sender: parse_address(&sender)?,
+                receiver,
+                token_id: token_id_from_row(creator, collection, name, property_version)?,
+                amount: from_sql_int(amount, "amount")?,
+            })
+        })
+        .collect()
+    }
+}
+
+/// The version of `transaction`, `None` for pending transactions.
+fn apply_transaction(tx: &Transaction, transaction: &Value) -> Result<Option<u64>> {
+    let version: u64 = match transaction["version"].as_str() {
+        Some(version) => version.parse()?,
+        None => return Ok(None),
+    };
+    if transaction["success"].as_bool() == Some(true) {
+        index_transaction(tx, version, transaction)
+            .with_context(|| format!("Failed to index transaction {}", version))?;
+    }
+    Ok(Some(version))
+}
+
+fn index_transaction(tx: &Transaction, version: u64, transaction: &Value) -> Result<()> {
+    let version = sql_int(version, "version")?;
+
+    if let Some(events) = transaction["events"].as_array() {
+        for event in events {
+            let event_type = event["type"].as_str().unwrap_or_default();
+            if !event_type.starts_with("0x3::token") {
+                continue;
+            }
+            let account = parse_address(event["guid"]["account_address"].as_str().unwrap_or_default())?;
+            index_event(tx, version, account, event_type, event["data"].clone())?;
+        }
+    }
+
+    if let Some(changes) = transaction["changes"].as_array() {
+        index_changes(tx, version, changes)?;
+    }
+
+    Ok(())
+}
+
+/// Token data and collection mutations, and cancelled swap listings, only
+/// show up as table changes.
+fn index_changes(tx: &Transaction, version: i64, changes: &[Value]) -> Result<()> {
+    // Table items don't say which account they belong to, so the tables of
+    // `Collections` and `TokenListings` resources are remembered first.
+    for change in changes {
+        if change["type"] != "write_resource" {
+            continue;
+        }
+        let resource_type = change["data"]["type"].as_str().unwrap_or_default();
+        let handle = if resource_type == "0x3::token::Collections" {
+            &change["data"]["data"]["collection_data"]["handle"]
+        } else if resource_type.starts_with(LISTINGS_RESOURCE_PREFIX) {
+            &change["data"]["data"]["listings"]["handle"]
+        } else {
+            continue;
+        };
+        tx.execute(
+            "INSERT OR REPLACE INTO table_owners (handle, owner, resource_type) VALUES (?1, ?2, ?3)",
+            params![
+                table_handle(handle)?,
+                parse_address(change["address"].as_str().unwrap_or_default())?.to_hex_literal(),
+                resource_type,
+            ],
+        )?;
+    }
+
+    for change in changes {
+        match (change["type"].as_str(), change["data"]["value_type"].as_str()) {
+            (Some("write_table_item"), Some("0x3::token::TokenData")) => {
+                let id: TokenDataId = serde_json::from_value(change["data"]["key"].clone())?;
+                let data: TokenData = serde_json::from_value(change["data"]["value"].clone())?;
+                tx.execute(
+                    "UPDATE token_datas SET description = ?4, uri = ?5, maximum = ?6, supply = ?7, last_version = ?8
+                     WHERE creator = ?1 AND collection = ?2 AND name = ?3",
+                    params![
+                        id.creator.to_hex_literal(),
+                        id.collection,
+                        id.name,
+                        data.description,
+                        data.uri,
+                        sql_int(data.maximum.map(|m| m.0).unwrap_or(0), "maximum")?,
+                        sql_int(data.supply.0, "supply")?,
+                        version,
+                    ],
+                )?;
+            }
+            (Some("write_table_item"), Some("0x3::token::CollectionData")) => {
+                let creator = match table_owner(tx, &change["handle"])? {
+                    Some((creator, _)) => creator,
+                    None => continue,
+                };
+                let data: CollectionData = serde_json::from_value(change["data"]["value"].clone())?;
+                tx.execute(
+                    "UPDATE collections SET description = ?3, uri = ?4, maximum = ?5, last_version = ?6
+                     WHERE creator = ?1 AND name = ?2",
+                    params![creator, data.name, data.description, data.uri, sql_int(data.maximum.0, "maximum")?, version],
+                )?;
+            }
+            (Some("delete_table_item"), _) if change["data"]["key_type"] == "0x3::token::TokenId" => {
+                let listing = table_owner(tx, &change["handle"])?.and_then(|(owner, resource_type)| {
+                    let coin_type = resource_type.strip_prefix(LISTINGS_RESOURCE_PREFIX)?.strip_suffix('>')?;
+                    Some((owner, coin_type.to_string()))
+                });
+                let (owner, coin_type) = match listing {
+                    Some(listing) => listing,
+                    None => continue,
+                };
+                let token_id: TokenId = serde_json::from_value(change["data"]["key"].clone())?;
+                let (creator, collection, name, property_version) = token_id_params(&token_id)?;
+                tx.execute(
+                    "DELETE FROM swap_listings WHERE owner = ?1 AND creator = ?2 AND collection = ?3 AND name = ?4
+                     AND property_version = ?5 AND coin_type = ?6",
+                    params![owner, creator, collection, name, property_version, coin_type],
+                )?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Table handles are addresses, normalized so that handles read from
+/// resources and from table changes compare equal.
+fn table_handle(handle: &Value) -> Result<String> {
+    let handle = handle.as_str().context("Table handle is not a string")?;
+    Ok(parse_address(handle)?.to_hex_literal())
+}
+
+/// The account and resource type holding the table `handle`, if it is one
+/// the indexer follows.
+fn table_owner(tx: &Transaction, handle: &Value) -> Result<Option<(String, String)>> {
+    Ok(tx
+        .query_row(
+            "SELECT owner, resource_type FROM table_owners WHERE handle = ?1",
+            params![table_handle(handle)?],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+fn index_event(tx: &Transaction, version: i64, account: AccountAddress, event_type: &str, data: Value) -> Result<()> {
+    let account = account.to_hex_literal();
+
+    match event_type {
+        "0x3::token::CreateCollectionEvent" => {
+            let event: CreateCollectionEvent = serde_json::from_value(data)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO collections (creator, name, description, uri, maximum, last_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    event.creator.to_hex_literal(),
+                    event.collection_name,
+                    event.description,
+                    event.uri,
+                    sql_int(event.maximum.0, "maximum")?,
+                    version,
+                ],
+            )?;
+        }
+        "0x3::token::CreateTokenDataEvent" => {
+            let event: CreateTokenDataEvent = serde_json::from_value(data)?;
+            tx.execute(
+                "INSERT OR IGNORE INTO token_datas (creator, collection, name, description, uri, maximum, last_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    event.id.creator.to_hex_literal(),
+                    event.id.collection,
+                    event.id.name,
+                    event.description,
+                    event.uri,
+                    sql_int(event.maximum.0, "maximum")?,
+                    version,
+                ],
+            )?;
+        }
+        "0x3::token::MintTokenEvent" => {
+            let event: MintTokenEvent = serde_json::from_value(data)?;
+            tx.execute(
+                "UPDATE token_datas SET supply = supply + ?4, last_version = ?5
+                 WHERE creator = ?1 AND collection = ?2 AND name = ?3",
+                params![
+                    event.id.creator.to_hex_literal(),
+                    event.id.collection,
+                    event.id.name,
+                    sql_int(event.amount.0, "amount")?,
+                    version,
+                ],
+            )?;
+        }
+        "0x3::token::BurnTokenEvent" => {
+            let event: BurnTokenEvent = serde_json::from_value(data)?;
+            let id = &event.id.token_data_id;
+            tx.execute(
+                "UPDATE token_datas SET supply = supply - ?4, last_version = ?5
+                 WHERE creator = ?1 AND collection = ?2 AND name = ?3",
+                params![id.creator.to_hex_literal(), id.collection, id.name, sql_int(event.amount.0, "amount")?, version],
+            )?;
+        }
+        "0x3::token::DepositEvent" => {
+            let event: DepositEvent = serde_json::from_value(data)?;
+            add_ownership(tx, version, &account, &event.id, sql_int(event.amount.0, "amount")?)?;
+        }
+        "0x3::token::WithdrawEvent" => {
+            let event: WithdrawEvent = serde_json::from_value(data)?;
+            add_ownership(tx, version, &account, &event.id, -sql_int(event.amount.0, "amount")?)?;
+        }
+        "0x3::token_transfers::TokenOfferEvent" => {
+            let event: TokenOfferEvent = serde_json::from_value(data)?;
+            let (creator, collection, name, property_version) = token_id_params(&event.token_id)?;
+            tx.execute(
+                "INSERT INTO pending_offers (sender, receiver, creator, collection, name, property_version, amount, last_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (sender, receiver, creator, collection, name, property_version)
+                 DO UPDATE SET amount = amount + excluded.amount, last_version = excluded.last_version",
+                params![
+                    account,
+                    event.to_address.to_hex_literal(),
+                    creator,
+                    collection,
+                    name,
+                    property_version,
+                    sql_int(event.amount.0, "amount")?,
+                    version,
+                ],
+            )?;
+        }
+        "0x3::token_transfers::TokenClaimEvent" => {
+            let event: TokenClaimEvent = serde_json::from_value(data)?;
+            remove_offer(tx, &account, event.to_address, &event.token_id)?;
+        }
+        "0x3::token_transfers::TokenCancelOfferEvent" => {
+            let event: TokenCancelOfferEvent = serde_json::from_value(data)?;
+            remove_offer(tx, &account, event.to_address, &event.token_id)?;
+        }
+        "0x3::token_coin_swap::TokenListingEvent" => {
+            let event: TokenListingEvent = serde_json::from_value(data)?;
+            let (creator, collection, name, property_version) = token_id_params(&event.token_id)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO swap_listings
+                 (owner, creator, collection, name, property_version, coin_type, amount, min_price, locked_until_secs, last_version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    account,
+                    creator,
+                    collection,
+                    name,
+                    property_version,
+                    event.coin_type_info.to_type_name()?,
+                    sql_int(event.amount.0, "amount")?,
+                    sql_int(event.min_price.0, "min price")?,
+                    sql_int(event.locked_until_secs.0, "locked until")?,
+                    version,
+                ],
+            )?;
+        }
+        "0x3::token_coin_swap::TokenSwapEvent" => {
+            let event: TokenSwapEvent = serde_json::from_value(data)?;
+            let (creator, collection, name, property_version) = token_id_params(&event.token_id)?;
+            let coin_type = event.coin_type_info.to_type_name()?;
+            tx.execute(
+                "UPDATE swap_listings SET amount = amount - ?7, last_version = ?8
+                 WHERE owner = ?1 AND creator = ?2 AND collection = ?3 AND name = ?4
+                 AND property_version = ?5 AND coin_type = ?6",
+                params![account, creator, collection, name, property_version, coin_type, sql_int(event.token_amount.0, "token amount")?, version],
+            )?;
+            tx.execute("DELETE FROM swap_listings WHERE amount <= 0", [])?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn add_ownership(tx: &Transaction, version: i64, owner: &str, token_id: &TokenId, delta: i64) -> Result<()> {
+    let (creator, collection, name, property_version) = token_id_params(token_id)?;
+    tx.execute(
+        "INSERT INTO ownerships (owner, creator, collection, name, property_version, amount, last_version)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT (owner, creator, collection, name, property_version)
+         DO UPDATE SET amount = amount + excluded.amount, last_version = excluded.last_version",
+        params![owner, creator, collection, name, property_version, delta, version],
+    )?;
+    Ok(())
+}
+
+fn remove_offer(tx: &Transaction, sender: &str, receiver: AccountAddress, token_id: &TokenId) -> Result<()> {
+    let (creator, collection, name, property_version) = token_id_params(token_id)?;
+    tx.execute(
+        "DELETE FROM pending_offers WHERE sender = ?1 AND receiver = ?2 AND creator = ?3
+         AND collection = ?4 AND name = ?5 AND property_version = ?6",
+        params![sender, receiver.to_hex_literal(), creator, collection, name, property_version],
+    )?;
+    Ok(())
+}
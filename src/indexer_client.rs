@@ -0,0 +1,393 @@
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{rest_client::aptos_api_types::U64, types::account_address::AccountAddress};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::{
+    accounts::parse_address,
+    types::{
+        CollectionData, CollectionMutabilityConfig, PendingOffer, PropertyMap, PropertyValue,
+        Royalty, SimpleMap, SimpleMapEntry, Token, TokenData, TokenDataId, TokenId,
+        TokenMutabilityConfig,
+    },
+};
+
+/// The public indexers cap how many rows a query returns, so list queries
+/// are paged.
+pub const DEFAULT_PAGE_SIZE: u64 = 100;
+
+const TOKEN_OWNERSHIPS_QUERY: &str = "
+query CurrentTokenOwnerships($owner: String!, $limit: Int!, $offset: Int!) {
+  current_token_ownerships(
+    where: {owner_address: {_eq: $owner}, amount: {_gt: 0}}
+    order_by: [{creator_address: asc}, {collection_name: asc}, {name: asc}, {property_version: asc}]
+    limit: $limit
+    offset: $offset
+  ) {
+    owner_address creator_address collection_name name property_version amount
+  }
+}";
+
+const COLLECTION_HOLDERS_QUERY: &str = "
+query CollectionHolders($creator: String!, $collection: String!, $limit: Int!, $offset: Int!) {
+  current_token_ownerships(
+    where: {creator_address: {_eq: $creator}, collection_name: {_eq: $collection}, amount: {_gt: 0}}
+    order_by: [{owner_address: asc}, {name: asc}, {property_version: asc}]
+    limit: $limit
+    offset: $offset
+  ) {
+    owner_address creator_address collection_name name property_version amount
+  }
+}";
+
+const TOKEN_ACTIVITIES_QUERY: &str = "
+query TokenActivities($creator: String!, $collection: String!, $name: String!, $limit: Int!, $offset: Int!) {
+  token_activities(
+    where: {creator_address: {_eq: $creator}, collection_name: {_eq: $collection}, name: {_eq: $name}}
+    order_by: [{transaction_version: asc}, {event_index: asc}]
+    limit: $limit
+    offset: $offset
+  ) {
+    transaction_version creator_address collection_name name property_version transfer_type from_address to_address token_amount coin_type coin_amount
+  }
+}";
+
+const PENDING_OFFERS_QUERY: &str = "
+query PendingOffers($receiver: String!, $limit: Int!, $offset: Int!) {
+  current_token_pending_claims(
+    where: {to_address: {_eq: $receiver}, amount: {_gt: 0}}
+    order_by: [{from_address: asc}, {creator_address: asc}, {collection_name: asc}, {name: asc}, {property_version: asc}]
+    limit: $limit
+    offset: $offset
+  ) {
+    from_address to_address creator_address collection_name name property_version amount
+  }
+}";
+
+const COLLECTION_DATA_QUERY: &str = "
+query CollectionData($creator: String!, $collection: String!) {
+  current_collection_datas(where: {creator_address: {_eq: $creator}, collection_name: {_eq: $collection}}) {
+    collection_name description metadata_uri supply maximum description_mutable uri_mutable maximum_mutable
+  }
+}";
+
+const TOKEN_DATA_QUERY: &str = "
+query TokenData($creator: String!, $collection: String!, $name: String!) {
+  current_token_datas(where: {creator_address: {_eq: $creator}, collection_name: {_eq: $collection}, name: {_eq: $name}}) {
+    name description metadata_uri supply maximum largest_property_version payee_address
+    royalty_points_numerator royalty_points_denominator maximum_mutable uri_mutable
+    royalty_mutable description_mutable properties_mutable default_properties
+  }
+}";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenHolder {
+    pub owner: AccountAddress,
+    pub token: Token,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenActivity {
+    pub transaction_version: u64,
+    pub token_id: TokenId,
+    /// The event type, e.g. `0x3::token::DepositEvent`.
+    pub transfer_type: String,
+    pub from_address: Option<AccountAddress>,
+    pub to_address: Option<AccountAddress>,
+    pub token_amount: u64,
+    pub coin_type: Option<String>,
+    pub coin_amount: Option<u64>,
+}
+
+/// Reads token state from an Aptos indexer GraphQL endpoint. Results use
+/// the same types as `TokenClient`, which reads authoritative state from a
+/// fullnode, so callers can switch between the two.
+#[derive(Clone, Debug)]
+pub struct IndexerClient {
+    client: reqwest::Client,
+    url: Url,
+    page_size: u64,
+}
+
+fn field<'v>(row: &'v Value, name: &str) -> Result<&'v Value> {
+    match row.get(name) {
+        Some(value) if !value.is_null() => Ok(value),
+        _ => bail!("Indexer response is missing `{}`", name),
+    }
+}
+
+fn string_field(row: &Value, name: &str) -> Result<String> {
+    field(row, name)?
+        .as_str()
+        .map(String::from)
+        .with_context(|| format!("Indexer field `{}` is not a string", name))
+}
+
+/// Numeric columns come back as JSON numbers or strings depending on the
+/// Hasura configuration. Numbers that are not exact integers are refused
+/// rather than rounded, since floats lose precision above 2^53.
+fn u64_field(row: &Value, name: &str) -> Result<u64> {
+    let value = field(row, name)?;
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .with_context(|| format!("Indexer field `{}` is not an unsigned integer", name)),
+        Value::String(s) => s
+            .parse()
+            .with_context(|| format!("Indexer field `{}` is not an unsigned integer", name)),
+        _ => bail!("Indexer field `{}` is not a number", name),
+    }
+}
+
+fn bool_field(row: &Value, name: &str) -> Result<bool> {
+    field(row, name)?
+        .as_bool()
+        .with_context(|| format!("Indexer field `{}` is not a boolean", name))
+}
+
+/// The indexer stores addresses in their full 64 character form, so the
+/// short `0x1` form never matches.
+fn address_variable(address: AccountAddress) -> String {
+    format!("0x{}", address.to_hex())
+}
+
+fn address_field(row: &Value, name: &str) -> Result<AccountAddress> {
+    parse_address(&string_field(row, name)?)
+}
+
+fn optional_address_field(row: &Value, name: &str) -> Result<Option<AccountAddress>> {
+    match row.get(name).and_then(Value::as_str) {
+        Some(address) => Ok(Some(parse_address(address)?)),
+        None => Ok(None),
+    }
+}
+
+fn token_id_from_row(row: &Value) -> Result<TokenId> {
+    Ok(TokenId {
+        token_data_id: TokenDataId {
+            creator: address_field(row, "creator_address")?,
+            collection: string_field(row, "collection_name")?,
+            name: string_field(row, "name")?,
+        },
+        property_version: U64(u64_field(row, "property_version")?),
+    })
+}
+
+fn token_from_row(row: &Value) -> Result<Token> {
+    Ok(Token {
+        id: token_id_from_row(row)?,
+        amount: U64(u64_field(row, "amount")?),
+    })
+}
+
+impl IndexerClient {
+    pub fn new(url: Url) -> Self {
+        Self { client: reqwest::Client::new(), url, page_size: DEFAULT_PAGE_SIZE }
+    }
+
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    async fn query(&self, query: &str, variables: Value, root: &str) -> Result<Vec<Value>> {
+        let response: Value = self
+            .client
+            .post(self.url.clone())
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .context("Failed to reach indexer")?
+            .error_for_status()
+            .context("Indexer returned an error status")?
+            .json()
+            .await
+            .context("Error on parsing indexer response")?;
+
+        if let Some(errors) = response.get("errors") {
+            bail!("Indexer query failed: {}", errors);
+        }
+        response["data"][root]
+            .as_array()
+            .cloned()
+            .with_context(|| format!("Indexer response has no `{}`", root))
+    }
+
+    /// Runs a query that takes `$limit` and `$offset` until a short page
+    /// comes back.
+    async fn query_all(&self, query: &str, mut variables: Value, root: &str) -> Result<Vec<Value>> {
+        let mut rows = Vec::new();
+        loop {
+            variables["limit"] = json!(self.page_size);
+            variables["offset"] = json!(rows.len());
+            let page = self.query(query, variables.clone(), root).await?;
+            let done = page.is_empty() || (page.len() as u64) < self.page_size;
+            rows.extend(page);
+            if done {
+                return Ok(rows);
+            }
+        }
+    }
+
+    pub async fn get_current_token_ownerships(&self, owner: AccountAddress) -> Result<Vec<Token>> {
+        self.query_all(
+            TOKEN_OWNERSHIPS_QUERY,
+            json!({ "owner": address_variable(owner) }),
+            "current_token_ownerships",
+        )
+        .await?
+        .iter()
+        .map(token_from_row)
+        .collect()
+    }
+
+    pub async fn get_collection_holders(
+        &self,
+        creator: AccountAddress,
+        collection: &str,
+    ) -> Result<Vec<TokenHolder>> {
+        self.query_all(
+            COLLECTION_HOLDERS_QUERY,
+            json!({ "creator": address_variable(creator), "collection": collection }),
+            "current_token_ownerships",
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(TokenHolder {
+                owner: address_field(row, "owner_address")?,
+                token: token_from_row(row)?,
+            })
+        })
+        .collect()
+    }
+
+    /// Every activity on a token data, oldest first.
+    pub async fn get_token_activities(&self, token_data_id: &TokenDataId) -> Result<Vec<TokenActivity>> {
+        self.query_all(
+            TOKEN_ACTIVITIES_QUERY,
+            json!({
+                "creator": address_variable(token_data_id.creator),
+                "collection": token_data_id.collection,
+                "name": token_data_id.name,
+            }),
+            "token_activities",
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(TokenActivity {
+                transaction_version: u64_field(row, "transaction_version")?,
+                token_id: token_id_from_row(row)?,
+                transfer_type: string_field(row, "transfer_type")?,
+                from_address: optional_address_field(row, "from_address")?,
+                to_address: optional_address_field(row, "to_address")?,
+                token_amount: u64_field(row, "token_amount")?,
+                coin_type: row.get("coin_type").and_then(Value::as_str).map(String::from),
+                coin_amount: u64_field(row, "coin_amount").ok(),
+            })
+        })
+        .collect()
+    }
+
+    pub async fn get_pending_offers(&self, receiver: AccountAddress) -> Result<Vec<PendingOffer>> {
+        self.query_all(
+            PENDING_OFFERS_QUERY,
+            json!({ "receiver": address_variable(receiver) }),
+            "current_token_pending_claims",
+        )
+        .await?
+        .iter()
+        .map(|row| {
+            Ok(PendingOffer {
+                sender: address_field(row, "from_address")?,
+                receiver: address_field(row, "to_address")?,
+                token: token_from_row(row)?,
+            })
+        })
+        .collect()
+    }
+
+    pub async fn get_collection_data(&self, creator: AccountAddress, collection: &str) -> Result<CollectionData> {
+        let rows = self
+            .query(
+                COLLECTION_DATA_QUERY,
+                json!({ "creator": address_variable(creator), "collection": collection }),
+                "current_collection_datas",
+            )
+            .await?;
+        let row = rows.first().context("Target collection not found in indexer")?;
+
+        Ok(CollectionData {
+            name: string_field(row, "collection_name")?,
+            description: string_field(row, "description")?,
+            uri: string_field(row, "metadata_uri")?,
+            supply: U64(u64_field(row, "supply")?),
+            maximum: U64(u64_field(row, "maximum")?),
+            mutability_config: CollectionMutabilityConfig {
+                description: bool_field(row, "description_mutable")?,
+                maximum: bool_field(row, "maximum_mutable")?,
+                uri: bool_field(row, "uri_mutable")?,
+            },
+        })
+    }
+
+    /// The indexer stores default properties decoded, so the returned
+    /// property values are plain strings with an empty type.
+    pub async fn get_token_data(
+        &self,
+        creator: AccountAddress,
+        collection: &str,
+        name: &str,
+    ) -> Result<TokenData> {
+        let rows = self
+            .query(
+                TOKEN_DATA_QUERY,
+                json!({ "creator": address_variable(creator), "collection": collection, "name": name }),
+                "current_token_datas",
+            )
+            .await?;
+        let row = rows.first().context("Target token not found in indexer")?;
+
+        let properties = match row.get("default_properties") {
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(key, value)| SimpleMapEntry {
+                    key: key.clone(),
+                    value: PropertyValue {
+                        value: value.as_str().map(String::from).unwrap_or_else(|| value.to_string()),
+                        type_: String::new(),
+                    },
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok(TokenData {
+            maximum: Some(U64(u64_field(row, "maximum")?)),
+            largest_property_version: U64(u64_field(row, "largest_property_version")?),
+            supply: U64(u64_field(row, "supply")?),
+            uri: string_field(row, "metadata_uri")?,
+            royalty: Royalty {
+                royalty_points_numerator: U64(u64_field(row, "royalty_points_numerator")?),
+                royalty_points_denominator: U64(u64_field(row, "royalty_points_denominator")?),
+                payee_address: address_field(row, "payee_address")?,
+            },
+            name: string_field(row, "name")?,
+            description: string_field(row, "description")?,
+            mutability_config: TokenMutabilityConfig {
+                maximum: bool_field(row, "maximum_mutable")?,
+                uri: bool_field(row, "uri_mutable")?,
+                royalty: bool_field(row, "royalty_mutable")?,
+                description: bool_field(row, "description_mutable")?,
+                properties: bool_field(row, "properties_mutable")?,
+            },
+            default_properties: PropertyMap { map: SimpleMap { data: properties } },
+        })
+    }
+}
@@ -0,0 +1,535 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use aptos_sdk::{
+    rest_client::error::RestError,
+    types::{
+        account_address::AccountAddress,
+        transaction::{SignedTransaction, TransactionPayload},
+        vm_status::StatusCode as VmStatusCode,
+    },
+};
+use futures::future::join_all;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    multi_agent::MultiAgentTransaction,
+    offline::{signed_transaction_from_hex, signed_transaction_to_hex},
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    signer::TransactionSigner,
+    types::TransactionOptions,
+    TokenClient,
+};
+
+/// How often the runner checks a submitted transaction.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A write a job performs, signed by the journal's runner.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum JobOperation {
+    CreateCollection(CreateCollectionRequest),
+    CreateToken(CreateTokenRequest),
+    MintToken {
+        collection: String,
+        name: String,
+        amount: u64,
+    },
+    BurnToken {
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+        property_version: u64,
+    },
+    OfferToken {
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+        property_version: u64,
+    },
+    CancelTokenOffer {
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        property_version: u64,
+    },
+    TransferWithOptIn {
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+        property_version: u64,
+    },
+    /// Co-signed by the receiver, see `JournalRunOptions::receivers`.
+    DirectTransfer {
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+        property_version: u64,
+    },
+}
+
+impl JobOperation {
+    pub fn payload(&self, client: &TokenClient, sender: AccountAddress) -> Result<TransactionPayload> {
+        match self {
+            JobOperation::CreateCollection(request) => client.create_collection_payload(request),
+            JobOperation::CreateToken(request) => client.create_token_payload(sender, request),
+            JobOperation::MintToken { collection, name, amount } => {
+                client.mint_token_payload(sender, collection, name, *amount)
+            }
+            JobOperation::BurnToken { creator, collection, name, amount, property_version } => {
+                client.burn_token_payload(*creator, collection, name, *amount, *property_version)
+            }
+            JobOperation::OfferToken { receiver, creator, collection, name, amount, property_version } => {
+                client.offer_token_payload(*receiver, *creator, collection, name, *amount, *property_version)
+            }
+            JobOperation::CancelTokenOffer { receiver, creator, collection, name, property_version } => {
+                client.cancel_token_offer_payload(*receiver, *creator, collection, name, *property_version)
+            }
+            JobOperation::TransferWithOptIn { receiver, creator, collection, name, amount, property_version } => {
+                client.transfer_with_opt_in_payload(*receiver, *creator, collection, name, *amount, *property_version)
+            }
+            JobOperation::DirectTransfer { creator, collection, name, amount, property_version, .. } => {
+                client.direct_transfer_token_payload(*creator, collection, name, *amount, *property_version)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobState {
+    Planned,
+    /// Signed and recorded, maybe not yet sent.
+    Signed {
+        hash: String,
+        expiration_timestamp_secs: u64,
+        /// Hex encoded BCS of the signed transaction, resent as is.
+        transaction: String,
+    },
+    Submitted {
+        hash: String,
+        expiration_timestamp_secs: u64,
+        transaction: String,
+    },
+    Succeeded {
+        hash: String,
+        version: u64,
+    },
+    Failed {
+        hash: String,
+        vm_status: String,
+    },
+}
+
+impl JobState {
+    pub fn is_final(&self) -> bool {
+        matches!(self, JobState::Succeeded { .. } | JobState::Failed { .. })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Job {
+    pub id: u64,
+    pub operation: JobOperation,
+    pub state: JobState,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JournalSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub remaining: usize,
+}
+
+pub struct JournalRunOptions<'s> {
+    pub transaction_options: Option<TransactionOptions>,
+    /// Jobs in flight at once.
+    pub concurrency: usize,
+    /// Co-signers of `DirectTransfer` jobs, by receiver.
+    pub receivers: HashMap<AccountAddress, &'s dyn TransactionSigner>,
+}
+
+impl Default for JournalRunOptions<'_> {
+    fn default() -> Self {
+        Self {
+            transaction_options: None,
+            concurrency: 1,
+            receivers: HashMap::new(),
+        }
+    }
+}
+
+/// Jobs and their progress, saved to a JSON file after every state change
+/// so a killed run can be resumed with `Journal::open`.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    jobs: Vec<Job>,
+}
+
+impl Journal {
+    pub fn create(path: impl AsRef<Path>, operations: Vec<JobOperation>) -> Result<Self> {
+        let mut journal = Self {
+            path: path.as_ref().to_path_buf(),
+            jobs: vec![],
+        };
+        for operation in operations {
+            journal.push(operation);
+        }
+        journal.save()?;
+        Ok(journal)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            jobs: serde_json::from_str(&content).context("Error on parsing journal")?,
+        })
+    }
+
+    /// Opens the journal at `path`, or creates it with `operations`.
+    pub fn open_or_create(path: impl AsRef<Path>, operations: Vec<JobOperation>) -> Result<Self> {
+        if path.as_ref().exists() {
+            Self::open(path)
+        } else {
+            Self::create(path, operations)
+        }
+    }
+
+    /// Adds a planned job; call `save` to persist it.
+    pub fn push(&mut self, operation: JobOperation) -> u64 {
+        let id = self.jobs.last().map(|job| job.id + 1).unwrap_or(0);
+        self.jobs.push(Job {
+            id,
+            operation,
+            state: JobState::Planned,
+        });
+        id
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn job(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    /// Writes to a temporary file first and syncs it to disk, so a crash
+    /// mid-write never leaves a truncated journal.
+    pub fn save(&self) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp_path).with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        file.write_all(serde_json::to_string_pretty(&self.jobs)?.as_bytes())
+            .and_then(|_| file.sync_all())
+            .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    pub fn summary(&self) -> JournalSummary {
+        let mut summary = JournalSummary::default();
+        for job in &self.jobs {
+            match job.state {
+                JobState::Succeeded { .. } => summary.succeeded += 1,
+                JobState::Failed { .. } => summary.failed += 1,
+                _ => summary.remaining += 1,
+            }
+        }
+        summary
+    }
+
+    /// Plans failed jobs again, e.g. after topping up the sender's balance.
+    pub fn retry_failed(&mut self) -> Result<()> {
+        for job in &mut self.jobs {
+            if matches!(job.state, JobState::Failed { .. }) {
+                job.state = JobState::Planned;
+            }
+        }
+        self.save()
+    }
+
+    fn set_state(&mut self, index: usize, state: JobState) -> Result<()> {
+        self.jobs[index].state = state;
+        self.save()
+    }
+}
+
+enum OnChainStatus {
+    NotFound,
+    Pending,
+    Committed { success: bool, version: u64, vm_status: String },
+}
+
+/// Why the node refused a transaction outright, rather than failing to
+/// take it in.
+enum Rejection {
+    /// Stale or too new sequence number, or already expired: the
+    /// transaction never ran and is signed again.
+    Resign,
+    /// Rejected for what it does or who sends it, e.g. too little gas.
+    Failed(String),
+}
+
+fn rejection(error: &RestError) -> Option<Rejection> {
+    let response = match error {
+        RestError::Api(response) if response.status_code.is_client_error() => response,
+        _ => return None,
+    };
+    let vm_error_code = response.error.vm_error_code?;
+    let resign = [
+        VmStatusCode::SEQUENCE_NUMBER_TOO_OLD,
+        VmStatusCode::SEQUENCE_NUMBER_TOO_NEW,
+        VmStatusCode::TRANSACTION_EXPIRED,
+    ];
+    if resign.iter().any(|code| *code as u64 == vm_error_code) {
+        Some(Rejection::Resign)
+    } else {
+        Some(Rejection::Failed(response.error.message.clone()))
+    }
+}
+
+impl<'a> TokenClient<'a> {
+    async fn get_ledger_timestamp_secs(&self) -> Result<u64> {
+        Ok(self
+            .api_client
+            .get_ledger_information()
+            .await
+            .context("Failed to get ledger information")?
+            .into_inner()
+            .timestamp_usecs
+            / 1_000_000)
+    }
+
+    async fn get_transaction_status(&self, hash: &str) -> Result<OnChainStatus> {
+        let url = self
+            .api_client
+            .build_path(&format!("transactions/by_hash/{}", hash))
+            .context("Failed to build transaction URL")?;
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Error on getting transaction {}", hash))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(OnChainStatus::NotFound);
+        }
+
+        let transaction: Value = response
+            .error_for_status()
+            .with_context(|| format!("Error on getting transaction {}", hash))?
+            .json()
+            .await
+            .context("Error on parsing transaction")?;
+        if transaction["type"] == "pending_transaction" {
+            return Ok(OnChainStatus::Pending);
+        }
+        Ok(OnChainStatus::Committed {
+            success: transaction["success"].as_bool().unwrap_or(false),
+            version: transaction["version"]
+                .as_str()
+                .and_then(|version| version.parse().ok())
+                .context("Committed transaction has no version")?,
+            vm_status: transaction["vm_status"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    async fn sign_job(
+        &self,
+        operation: &JobOperation,
+        account: &mut dyn TransactionSigner,
+        options: &JournalRunOptions<'_>,
+    ) -> Result<SignedTransaction> {
+        let payload = operation.payload(self, account.address())?;
+        match operation {
+            JobOperation::DirectTransfer { receiver, .. } => {
+                let receiver_signer = options
+                    .receivers
+                    .get(receiver)
+                    .with_context(|| format!("No signer for direct transfer receiver {}", receiver.to_hex_literal()))?;
+                let transaction_options = options.transaction_options.clone().unwrap_or_default();
+
+                let checked = self.check_write_online(account.address(), &payload, &[*receiver]).await?;
+                let signed_txn: Result<SignedTransaction> = async {
+                    let raw_txn = self
+                        .module_client
+                        .build_online_raw_transaction(account, payload, &transaction_options)
+                        .await?;
+                    let mut txn = MultiAgentTransaction::new(raw_txn, vec![*receiver]);
+                    txn.sign(&*account).await?;
+                    txn.sign(*receiver_signer).await?;
+                    txn.into_signed_transaction()
+                }
+                .await;
+                if signed_txn.is_err() {
+                    self.release_write(checked);
+                }
+                signed_txn
+            }
+            _ => self.sign_payload(account, payload, options.transaction_options.clone()).await,
+        }
+    }
+
+    /// Drives a signed or submitted job until it commits, or until it
+    /// expires unseen, in which case it is planned again.
+    async fn settle_job(&self, mut state: JobState) -> Result<JobState> {
+        loop {
+            let (hash, expiration_timestamp_secs, transaction, submitted) = match &state {
+                JobState::Signed { hash, expiration_timestamp_secs, transaction } => {
+                    (hash.clone(), *expiration_timestamp_secs, transaction.clone(), false)
+                }
+                JobState::Submitted { hash, expiration_timestamp_secs, transaction } => {
+                    (hash.clone(), *expiration_timestamp_secs, transaction.clone(), true)
+                }
+                _ => return Ok(state),
+            };
+
+            // Expiration is checked by the chain's clock, not ours. The clock
+            // is read before the lookup: a transaction not found after the
+            // ledger passed its expiration can never commit.
+            let ledger_timestamp_secs = self.get_ledger_timestamp_secs().await?;
+            match self.get_transaction_status(&hash).await? {
+                OnChainStatus::Committed { success: true, version, .. } => {
+                    return Ok(JobState::Succeeded { hash, version })
+                }
+                OnChainStatus::Committed { vm_status, .. } => return Ok(JobState::Failed { hash, vm_status }),
+                OnChainStatus::NotFound if ledger_timestamp_secs >= expiration_timestamp_secs => {
+                    return Ok(JobState::Planned)
+                }
+                // Accepted but not visible yet; it expires and is planned
+                // again if the mempool dropped it.
+                OnChainStatus::Pending | OnChainStatus::NotFound if submitted => {
+                    tokio::time::sleep(POLL_INTERVAL).await
+                }
+                OnChainStatus::Pending => tokio::time::sleep(POLL_INTERVAL).await,
+                OnChainStatus::NotFound => {
+                    let signed_txn = signed_transaction_from_hex(&transaction)?;
+                    match self.api_client.submit(&signed_txn).await {
+                        Ok(_) => state = JobState::Submitted { hash, expiration_timestamp_secs, transaction },
+                        Err(e) => match rejection(&e) {
+                            // A stale sequence number may mean it committed
+                            // since the lookup; if not, its sequence number
+                            // is taken and it can never run.
+                            Some(Rejection::Resign) => {
+                                if let OnChainStatus::NotFound = self.get_transaction_status(&hash).await? {
+                                    return Ok(JobState::Planned);
+                                }
+                            }
+                            Some(Rejection::Failed(vm_status)) => {
+                                return Ok(JobState::Failed {
+                                    hash,
+                                    vm_status: format!("Rejected on submission: {}", vm_status),
+                                })
+                            }
+                            // The node may have taken it in anyway; look the
+                            // hash up again until it expires.
+                            None => tokio::time::sleep(POLL_INTERVAL).await,
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    /// Same as `run_journal_with`, one job at a time.
+    pub async fn run_journal(
+        &self,
+        journal: &mut Journal,
+        account: &mut dyn TransactionSigner,
+        options: Option<TransactionOptions>,
+    ) -> Result<JournalSummary> {
+        let options = JournalRunOptions {
+            transaction_options: options,
+            ..Default::default()
+        };
+        self.run_journal_with(journal, account, options).await
+    }
+
+    /// Runs every unfinished job of `journal`, in order and up to
+    /// `options.concurrency` at a time, and returns once each has succeeded
+    /// or failed.
+    ///
+    /// Jobs left signed or submitted by an earlier run are looked up by
+    /// hash and settled before anything new is signed: committed ones are
+    /// recorded, unexpired ones are resent, and expired ones, or ones the
+    /// node refused for their sequence number, are signed again. `account`'s sequence number is reset from the chain before
+    /// each batch, so expired jobs leave no gaps.
+    pub async fn run_journal_with(
+        &self,
+        journal: &mut Journal,
+        account: &mut dyn TransactionSigner,
+        options: JournalRunOptions<'_>,
+    ) -> Result<JournalSummary> {
+        let concurrency = options.concurrency.max(1);
+        loop {
+            let in_flight: Vec<usize> = (0..journal.jobs.len())
+                .filter(|&index| matches!(journal.jobs[index].state, JobState::Signed { .. } | JobState::Submitted { .. }))
+                .collect();
+            for batch in in_flight.chunks(concurrency) {
+                let states = join_all(
+                    batch
+                        .iter()
+                        .map(|&index| self.settle_job(journal.jobs[index].state.clone())),
+                )
+                .await;
+                for (&index, state) in batch.iter().zip(states) {
+                    let state = state.with_context(|| format!("Failed to settle job {}", journal.jobs[index].id))?;
+                    journal.set_state(index, state)?;
+                }
+            }
+
+            let planned: Vec<usize> = (0..journal.jobs.len())
+                .filter(|&index| journal.jobs[index].state == JobState::Planned)
+                .take(concurrency)
+                .collect();
+            if planned.is_empty() {
+                break;
+            }
+
+            let sequence_number = self
+                .api_client
+                .get_account(account.address())
+                .await
+                .with_context(|| format!("Failed to get account {}", account.address().to_hex_literal()))?
+                .into_inner()
+                .sequence_number;
+            account.set_sequence_number(sequence_number);
+
+            for index in planned {
+                let signed_txn = self
+                    .sign_job(&journal.jobs[index].operation, account, &options)
+                    .await
+                    .with_context(|| format!("Failed to sign job {}", journal.jobs[index].id))?;
+                journal.set_state(
+                    index,
+                    JobState::Signed {
+                        hash: signed_txn.clone().committed_hash().to_hex_literal(),
+                        expiration_timestamp_secs: signed_txn.expiration_timestamp_secs(),
+                        transaction: signed_transaction_to_hex(&signed_txn)?,
+                    },
+                )?;
+            }
+        }
+
+        Ok(journal.summary())
+    }
+}
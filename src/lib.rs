@@ -1,19 +1,54 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr, time::{SystemTime, UNIX_EPOCH}};
 
 use anyhow::{Context, Result};
 use aptos_sdk::{
+    crypto::ed25519::{Ed25519PublicKey, Ed25519Signature, ED25519_SIGNATURE_LENGTH},
     rest_client::{Client as ApiClient, PendingTransaction, aptos_api_types::U64},
     types::{
-        LocalAccount,
         account_address::AccountAddress,
+        transaction::{SignedTransaction, TransactionPayload},
     },
     bcs, move_types::language_storage::TypeTag,
 };
 
 pub mod types;
+pub mod accounts;
+pub mod airdrop;
+pub mod claimer;
+pub mod events;
+pub mod explain;
+mod history;
+pub mod journal;
+#[cfg(feature = "indexer")]
+pub mod indexer;
+pub mod indexer_client;
+mod listing;
+pub mod manifest;
+mod offers;
+pub mod network;
+pub mod requests;
+pub mod validation;
+pub mod signer;
+pub mod snapshot;
+pub mod offline;
+pub mod operation;
+pub mod policy;
+pub mod multi_agent;
+pub mod multisig;
+pub mod multi_key;
 mod module_client;
+use events::get_all_events;
 use module_client::ModuleClient;
+use multi_agent::MultiAgentTransaction;
+use multi_key::{MultiEd25519Account, MultiEd25519Transaction};
+use offline::{payload_function_name, UnsignedTransaction};
+use network::{MainnetConfirmation, Network, MAINNET_CHAIN_ID};
+use operation::TokenOperation;
+use policy::{PolicyContext, SigningPolicy};
+use requests::{CreateCollectionRequest, CreateTokenRequest};
+use signer::TransactionSigner;
 use types::*;
+use validation::{validate_properties, validate_token_args};
 
 const fn get_hex_address_three() -> AccountAddress {
     let mut addr = [0u8; AccountAddress::LENGTH];
@@ -22,10 +57,22 @@ const fn get_hex_address_three() -> AccountAddress {
 }
 
 #[derive(Clone, Debug)]
+/// An operation the signing policy accepted, with its context.
+struct CheckedWrite {
+    context: PolicyContext,
+    operation: TokenOperation,
+}
+
 pub struct TokenClient<'a> {
     api_client: &'a ApiClient,
+    http_client: reqwest::Client,
+    chain_id: u8,
     module_client: ModuleClient,
     token_transfer_module_client: ModuleClient,
+    token_coin_swap_module_client: ModuleClient,
+    multisig_account_module_client: ModuleClient,
+    mainnet_confirmation: Option<MainnetConfirmation>,
+    policy: Option<SigningPolicy>,
 }
 
 impl<'a> TokenClient<'a> {
@@ -36,6 +83,12 @@ impl<'a> TokenClient<'a> {
             .context("Failed to get chain ID")?
             .inner()
             .chain_id;
+        Ok(Self::with_chain_id(api_client, chain_id))
+    }
+
+    /// Same as `new`, without asking the node for its chain ID, e.g. to
+    /// build payloads and transactions offline.
+    pub fn with_chain_id(api_client: &'a ApiClient, chain_id: u8) -> TokenClient<'a> {
         let module_client = ModuleClient::new(
             chain_id, 
             get_hex_address_three(),
@@ -46,106 +99,234 @@ impl<'a> TokenClient<'a> {
             get_hex_address_three(),
             "token_transfers"
         );
-        Ok(Self { 
+        let token_coin_swap_module_client = ModuleClient::new(
+            chain_id, 
+            get_hex_address_three(),
+            "token_coin_swap"
+        );
+        Self { 
             api_client, 
+            http_client: reqwest::Client::new(),
+            chain_id,
             module_client,
             token_transfer_module_client,
-        })
+            token_coin_swap_module_client,
+            multisig_account_module_client: multisig::multisig_account_module_client(chain_id),
+            mainnet_confirmation: None,
+            policy: None,
+        }
     }
 
-    pub async fn create_collection_script(
+    /// Same as `new`, but fails if the node's chain ID is not the one
+    /// expected for `network`.
+    pub async fn from_network(api_client: &'a ApiClient, network: &Network) -> Result<TokenClient<'a>> {
+        let client = Self::new(api_client).await?;
+        network.check_chain_id(client.chain_id)?;
+        Ok(client)
+    }
+
+    /// Registers a hook that runs before any write method signs a
+    /// transaction while connected to mainnet.
+    pub fn with_mainnet_confirmation<F>(mut self, confirm: F) -> Self
+    where
+        F: Fn(&str) -> Result<()> + Send + Sync + 'static,
+    {
+        self.mainnet_confirmation = Some(MainnetConfirmation::new(confirm));
+        self
+    }
+
+    /// Checks every transaction against `policy` before it is signed or,
+    /// for `build_unsigned_transaction`, handed out to be signed elsewhere.
+    /// Rejections are `PolicyError`s, reachable with `downcast_ref`.
+    pub fn with_policy(mut self, policy: SigningPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn chain_id(&self) -> u8 {
+        self.chain_id
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.chain_id == MAINNET_CHAIN_ID
+    }
+
+    /// Runs the signing policy, then the mainnet confirmation, before
+    /// `payload` is signed. `secondary_signers` are the other signers of a
+    /// multi-agent transaction. Offline, approvals of multisig proposals
+    /// are checked without the proposal; see `check_write_online`.
+    fn check_write(
         &self,
-        from_account: &mut LocalAccount,
-        name: &str,
-        description: &str,
-        uri: &str,
-        max_supply: u64,
-        options: Option<TransactionOptions>,
-        collection_options: Option<CollectionOptions>,
-    ) -> Result<PendingTransaction> {
-        let options = options.unwrap_or_default();
-        let collection_options = collection_options.unwrap_or_default();
-        
-        let signed_txn = self.module_client.build_signed_transaction(
-            from_account,
+        sender: AccountAddress,
+        payload: &TransactionPayload,
+        secondary_signers: &[AccountAddress],
+    ) -> Result<Option<CheckedWrite>> {
+        let operation = match &self.policy {
+            Some(_) => Some(TokenOperation::from_payload(payload, secondary_signers)?),
+            None => None,
+        };
+        self.check_operation(sender, payload, operation)
+    }
+
+    /// Same as `check_write`, reading the proposal a multisig vote is about
+    /// so the policy checks what an approval runs. Pass the result to
+    /// `release_write` if signing fails.
+    async fn check_write_online(
+        &self,
+        sender: AccountAddress,
+        payload: &TransactionPayload,
+        secondary_signers: &[AccountAddress],
+    ) -> Result<Option<CheckedWrite>> {
+        let operation = match &self.policy {
+            Some(_) => Some(
+                self.resolve_multisig_vote(TokenOperation::from_payload(payload, secondary_signers)?)
+                    .await?,
+            ),
+            None => None,
+        };
+        self.check_operation(sender, payload, operation)
+    }
+
+    fn check_operation(
+        &self,
+        sender: AccountAddress,
+        payload: &TransactionPayload,
+        operation: Option<TokenOperation>,
+    ) -> Result<Option<CheckedWrite>> {
+        let checked = match (&self.policy, operation) {
+            (Some(policy), Some(operation)) => {
+                let context = PolicyContext {
+                    sender,
+                    chain_id: self.chain_id,
+                };
+                policy.check(&context, &operation)?;
+                Some(CheckedWrite { context, operation })
+            }
+            _ => None,
+        };
+
+        let function_name = payload_function_name(payload);
+        if let Some(confirmation) = self.mainnet_confirmation.as_ref().filter(|_| self.is_mainnet()) {
+            if let Err(e) = confirmation.confirm(&function_name) {
+                // A declined transaction must not use up rate limits.
+                self.release_write(checked);
+                return Err(e).with_context(|| format!("Mainnet confirmation rejected `{}`", function_name));
+            }
+        }
+        Ok(checked)
+    }
+
+    /// Gives back what `check_write` took from the signing policy, for a
+    /// transaction that was not signed after all.
+    fn release_write(&self, checked: Option<CheckedWrite>) {
+        if let (Some(policy), Some(checked)) = (&self.policy, checked) {
+            policy.release(&checked.context, &checked.operation);
+        }
+    }
+
+    pub fn create_collection_payload(&self, request: &CreateCollectionRequest) -> Result<TransactionPayload> {
+        request.validate()?;
+
+        Ok(self.module_client.entry_function(
             "create_collection_script",
             vec![],
             vec![
-                bcs::to_bytes(name).unwrap(),        // Name
-                bcs::to_bytes(description).unwrap(), // Description
-                bcs::to_bytes(uri).unwrap(),         // Uri
-                bcs::to_bytes(&max_supply).unwrap(), // Total Supply ?
+                bcs::to_bytes(&request.name).unwrap(),        // Name
+                bcs::to_bytes(&request.description).unwrap(), // Description
+                bcs::to_bytes(&request.uri).unwrap(),         // Uri
+                bcs::to_bytes(&request.maximum).unwrap(),     // Maximum supply, 0 for unlimited
                 bcs::to_bytes(&vec![
-                    collection_options.description_mutable, // Description mutable ?
-                    collection_options.uri_mutable,         // URI mutable ?
-                    collection_options.supply_mutable,      // Maximum amount mutable ?
+                    request.mutability.description_mutable, // Description mutable ?
+                    request.mutability.uri_mutable,         // URI mutable ?
+                    request.mutability.supply_mutable,      // Maximum amount mutable ?
                 ])
                 .unwrap(),
             ],
-            options,
-        );
+        ))
+    }
 
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
+    pub async fn create_collection_script(
+        &self,
+        from_account: &mut dyn TransactionSigner,
+        request: &CreateCollectionRequest,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.create_collection_payload(request)?;
+
+        self.sign_and_submit(from_account, payload, options)
             .await
-            .context("Failed to submit create collection transaction")?
-            .into_inner()
-        )
+            .context("Failed to submit create collection transaction")
+    }
+
+    /// `creator` is the royalty payee when the request does not name one.
+    pub fn create_token_payload(
+        &self,
+        creator: AccountAddress,
+        request: &CreateTokenRequest,
+    ) -> Result<TransactionPayload> {
+        request.validate()?;
+        let royalty_payee = request.royalty_payee.unwrap_or(creator);
+
+        Ok(self.module_client.entry_function(
+            "create_token_script",
+            vec![],
+                vec![
+                bcs::to_bytes(&request.collection).unwrap(),
+                bcs::to_bytes(&request.name).unwrap(),
+                bcs::to_bytes(&request.description).unwrap(),
+                bcs::to_bytes(&request.supply).unwrap(),
+                bcs::to_bytes(&request.maximum).unwrap(),
+                bcs::to_bytes(&request.uri).unwrap(),
+                bcs::to_bytes(&royalty_payee).unwrap(),
+                bcs::to_bytes(&request.royalty.denominator).unwrap(),
+                bcs::to_bytes(&request.royalty.numerator).unwrap(),
+                bcs::to_bytes(&request.mutability.to_vec()).unwrap(),
+                bcs::to_bytes(&request.properties.keys).unwrap(),
+                bcs::to_bytes(&request.properties.values).unwrap(),
+                bcs::to_bytes(&request.properties.types).unwrap(),
+            ],
+        ))
     }
 
     pub async fn create_token(
         &self,
-        account: &mut LocalAccount,
-        collection_name: &str,
-        name: &str,
-        description: &str,
-        supply: u64,
-        uri: &str,
-        max_mint: u64,
-        royalty_payee: Option<AccountAddress>,
-        royalty_points: Option<RoyaltyPoints>,
-        property: Option<TokenProperty>,
+        account: &mut dyn TransactionSigner,
+        request: &CreateTokenRequest,
         options: Option<TransactionOptions>,
     ) -> Result<PendingTransaction> {
-        let options = options.unwrap_or_default();
-        let property = property.unwrap_or_default();
-        let royalty_points = royalty_points.unwrap_or_default();
-        let royalty_payee = royalty_payee.unwrap_or(account.address());
+        let payload = self.create_token_payload(account.address(), request)?;
 
-        let signed_txn = self.module_client.build_signed_transaction(
-            account,
-            "create_token_script",
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit create token transaction")
+    }
+
+    pub fn burn_token_payload(
+        &self,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+
+        Ok(self.module_client.entry_function(
+            "burn",
             vec![],
                 vec![
+                bcs::to_bytes(&creator).unwrap(),
                 bcs::to_bytes(collection_name).unwrap(),
                 bcs::to_bytes(name).unwrap(),
-                bcs::to_bytes(description).unwrap(),
-                bcs::to_bytes(&supply).unwrap(),
-                bcs::to_bytes(&max_mint).unwrap(),
-                bcs::to_bytes(uri).unwrap(),
-                bcs::to_bytes(&royalty_payee).unwrap(),
-                bcs::to_bytes(&royalty_points.denominator).unwrap(),
-                bcs::to_bytes(&royalty_points.numerator).unwrap(),
-                bcs::to_bytes(&vec![false, false, false, false, false]).unwrap(),
-                bcs::to_bytes(&property.keys).unwrap(),
-                bcs::to_bytes(&property.values).unwrap(),
-                bcs::to_bytes(&property.types).unwrap(),
+                bcs::to_bytes(&property_version).unwrap(),
+                bcs::to_bytes(&amount).unwrap(),
             ],
-            options);
-
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
-            .await
-            .context("Failed to submit create token transaction")?
-            .into_inner()
-        )
+        ))
     }
 
     pub async fn burn_token(
         &self,
-        account: &mut LocalAccount,
+        account: &mut dyn TransactionSigner,
         creator: AccountAddress,
         collection_name: &str,
         name: &str,
@@ -153,30 +334,107 @@ impl<'a> TokenClient<'a> {
         property_version: Option<u64>,
         options: Option<TransactionOptions>,
     ) -> Result<PendingTransaction> {
-        let options = options.unwrap_or_default();
         let property_version = property_version.unwrap_or_default();
+        let payload = self.burn_token_payload(creator, collection_name, name, amount, property_version)?;
 
-        let signed_txn = self.module_client.build_signed_transaction(
-            account,
-            "burn",
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit burn token transaction")
+    }
+
+    /// Mints `amount` more of a token the signer created, up to its maximum.
+    pub fn mint_token_payload(
+        &self,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+
+        Ok(self.module_client.entry_function(
+            "mint_script",
             vec![],
-                vec![
+            vec![
+                bcs::to_bytes(&creator).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
+                bcs::to_bytes(&amount).unwrap(),
+            ],
+        ))
+    }
+
+    pub async fn mint_token(
+        &self,
+        account: &mut dyn TransactionSigner,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.mint_token_payload(account.address(), collection_name, name, amount)?;
+
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit mint token transaction")
+    }
+
+    /// Sets properties on `amount` of `token_owner`'s tokens. Signed by the
+    /// creator; tokens at property version 0 move to a new property version.
+    pub fn mutate_token_properties_payload(
+        &self,
+        token_owner: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        property_version: u64,
+        amount: u64,
+        properties: &TokenProperty,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+        validate_properties(properties)?;
+
+        Ok(self.module_client.entry_function(
+            "mutate_token_properties",
+            vec![],
+            vec![
+                bcs::to_bytes(&token_owner).unwrap(),
                 bcs::to_bytes(&creator).unwrap(),
                 bcs::to_bytes(collection_name).unwrap(),
                 bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
                 bcs::to_bytes(&amount).unwrap(),
+                bcs::to_bytes(&properties.keys).unwrap(),
+                bcs::to_bytes(&properties.values).unwrap(),
+                bcs::to_bytes(&properties.types).unwrap(),
             ],
-            options
-        );
+        ))
+    }
 
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
+    pub async fn mutate_token_properties(
+        &self,
+        account: &mut dyn TransactionSigner,
+        token_owner: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        property_version: u64,
+        amount: u64,
+        properties: &TokenProperty,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.mutate_token_properties_payload(
+            token_owner,
+            account.address(),
+            collection_name,
+            name,
+            property_version,
+            amount,
+            properties,
+        )?;
+
+        self.sign_and_submit(account, payload, options)
             .await
-            .context("Failed to submit burn token transaction")?
-            .into_inner()
-        )
+            .context("Failed to submit mutate token properties transaction")
     }
 
     pub async fn get_collection_data(&self, account: AccountAddress, collection_name: String) -> Result<CollectionData>{
@@ -270,6 +528,70 @@ impl<'a> TokenClient<'a> {
         Ok(token)
     }
 
+    /// Every token `account` holds, found by replaying its deposit and
+    /// withdraw events and confirming each positive balance against the
+    /// token store table.
+    pub async fn list_tokens_owned(&self, account: AccountAddress) -> Result<Vec<Token>> {
+        let resource = self
+            .api_client
+            .get_account_resource(
+                account,
+                "0x3::token::TokenStore"
+            )
+            .await
+            .context("Error on getting account resource")?
+            .into_inner();
+        let resource = match resource {
+            Some(resource) => resource,
+            None => return Ok(vec![]),
+        };
+
+        let data = serde_json::from_str::<TokenStoreResources>(
+            &resource.data.to_string()
+        ).context("Error on parsing token store resources")?;
+
+        let deposits = get_all_events::<DepositEvent>(
+            self.api_client,
+            account,
+            "0x3::token::TokenStore",
+            "deposit_events",
+            data.deposit_events.counter.0,
+        ).await?;
+        let withdraws = get_all_events::<WithdrawEvent>(
+            self.api_client,
+            account,
+            "0x3::token::TokenStore",
+            "withdraw_events",
+            data.withdraw_events.counter.0,
+        ).await?;
+
+        let mut balances = HashMap::<TokenId, i128>::new();
+        for event in deposits {
+            *balances.entry(event.data.id).or_default() += event.data.amount.0 as i128;
+        }
+        for event in withdraws {
+            *balances.entry(event.data.id).or_default() -= event.data.amount.0 as i128;
+        }
+
+        let mut tokens = Vec::new();
+        for (token_id, balance) in balances {
+            if balance <= 0 {
+                continue;
+            }
+            let item = self
+                .try_get_table_item(data.tokens.handle, "0x3::token::TokenId", "0x3::token::Token", &token_id)
+                .await?;
+            // Events can disagree with the table, e.g. after a property
+            // mutation, so the table is authoritative: a missing item means
+            // the token is not held.
+            if let Some(item) = item {
+                tokens.push(serde_json::from_value::<Token>(item).context("Error on parsing token")?);
+            }
+        }
+
+        Ok(tokens)
+    }
+
     pub async fn get_token_data(
         &self,
         creator: AccountAddress,
@@ -311,9 +633,34 @@ impl<'a> TokenClient<'a> {
         Ok(token_data)
     }
 
+    pub fn offer_token_payload(
+        &self,
+        to_account: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+
+        Ok(self.token_transfer_module_client.entry_function(
+            "offer_script",
+            vec![],
+                vec![
+                bcs::to_bytes(&to_account).unwrap(),
+                bcs::to_bytes(&creator).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
+                bcs::to_bytes(&property_version).unwrap(),
+                bcs::to_bytes(&amount).unwrap(),
+            ],
+        ))
+    }
+
     pub async fn offer_token(
         &self,
-        from_account: &mut LocalAccount,
+        from_account: &mut dyn TransactionSigner,
         to_account: AccountAddress,
         creator: AccountAddress,
         collection_name: String,
@@ -323,34 +670,46 @@ impl<'a> TokenClient<'a> {
         options: Option<TransactionOptions>,
     ) -> Result<PendingTransaction> {
         let property_version = property_version.unwrap_or(0);
-        let options = options.unwrap_or_default();
+        let payload = self.offer_token_payload(
+            to_account,
+            creator,
+            &collection_name,
+            &name,
+            amount,
+            property_version,
+        )?;
 
-        let signed_txn = self.token_transfer_module_client.build_signed_transaction(
-            from_account,
-            "offer_script",
+        self.sign_and_submit(from_account, payload, options)
+            .await
+            .context("Failed to submit offer token transaction")
+    }
+
+    pub fn claim_token_payload(
+        &self,
+        sender: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, None)?;
+
+        Ok(self.token_transfer_module_client.entry_function(
+            "claim_script",
             vec![],
                 vec![
-                bcs::to_bytes(&to_account).unwrap(),
+                bcs::to_bytes(&sender).unwrap(),
                 bcs::to_bytes(&creator).unwrap(),
-                bcs::to_bytes(&collection_name).unwrap(),
-                bcs::to_bytes(&name).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
-                bcs::to_bytes(&amount).unwrap(),
             ],
-            options);
-
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
-            .await
-            .context("Failed to submit offer token transaction")?
-            .into_inner()
-        )
+        ))
     }
     
     pub async fn claim_token(
         &self,
-        account: &mut LocalAccount,
+        account: &mut dyn TransactionSigner,
         sender: AccountAddress,
         creator: AccountAddress,
         collection_name: String,
@@ -359,33 +718,39 @@ impl<'a> TokenClient<'a> {
         options: Option<TransactionOptions>,
     ) -> Result<PendingTransaction> {
         let property_version = property_version.unwrap_or(0);
-        let options = options.unwrap_or_default();
+        let payload = self.claim_token_payload(sender, creator, &collection_name, &name, property_version)?;
 
-        let signed_txn = self.token_transfer_module_client.build_signed_transaction(
-            account,
-            "claim_script",
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit claim token transaction")
+    }
+
+    pub fn cancel_token_offer_payload(
+        &self,
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, None)?;
+
+        Ok(self.token_transfer_module_client.entry_function(
+            "cancel_offer_script",
             vec![],
                 vec![
-                bcs::to_bytes(&sender).unwrap(),
+                bcs::to_bytes(&receiver).unwrap(),
                 bcs::to_bytes(&creator).unwrap(),
-                bcs::to_bytes(&collection_name).unwrap(),
-                bcs::to_bytes(&name).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
             ],
-            options);
-
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
-            .await
-            .context("Failed to submit claim token transaction")?
-            .into_inner()
-        )
+        ))
     }
 
     pub async fn cancel_token_offer(
         &self,
-        account: &mut LocalAccount,
+        account: &mut dyn TransactionSigner,
         receiver: AccountAddress,
         creator: AccountAddress,
         collection_name: String,
@@ -394,33 +759,42 @@ impl<'a> TokenClient<'a> {
         options: Option<TransactionOptions>,
     ) -> Result<PendingTransaction> {
         let property_version = property_version.unwrap_or(0);
-        let options = options.unwrap_or_default();
-        let signed_txn = self.token_transfer_module_client.build_signed_transaction(
-            account,
-            "cancel_offer_script",
+        let payload = self.cancel_token_offer_payload(receiver, creator, &collection_name, &name, property_version)?;
+
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit cancel token offer transaction")
+    }
+
+    /// Payload of a multi-agent transaction: the receiver must sign as a
+    /// secondary signer.
+    pub fn direct_transfer_token_payload(
+        &self,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+
+        Ok(self.module_client.entry_function(
+            "direct_transfer_script",
             vec![],
                 vec![
-                bcs::to_bytes(&receiver).unwrap(),
                 bcs::to_bytes(&creator).unwrap(),
-                bcs::to_bytes(&collection_name).unwrap(),
-                bcs::to_bytes(&name).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
+                bcs::to_bytes(&amount).unwrap(),
             ],
-            options);
-
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
-            .await
-            .context("Failed to submit cancel token offer transaction")?
-            .into_inner()
-        )
+        ))
     }
 
     pub async fn direct_transfer_token(
         &self,
-        account: &mut LocalAccount,
-        receiver: &mut LocalAccount,
+        account: &mut dyn TransactionSigner,
+        receiver: &dyn TransactionSigner,
         creator: AccountAddress,
         collection_name: String,
         name: String,
@@ -430,78 +804,399 @@ impl<'a> TokenClient<'a> {
     ) -> Result<PendingTransaction> {
         let property_version = property_version.unwrap_or_default();
         let options = options.unwrap_or_default();
+        let payload = self.direct_transfer_token_payload(creator, &collection_name, &name, amount, property_version)?;
 
-        let mut signers = Vec::<&LocalAccount>::new();
-        signers.push(receiver);
+        let signers = vec![receiver];
 
-        let signed_txn = self.module_client.build_multisigned_transaction(
+        let checked = self.check_write_online(account.address(), &payload, &[receiver.address()]).await?;
+        let signed_txn = match self.module_client.build_multisigned_transaction(
             account,
             signers,
-            "direct_transfer_script",
+            payload,
+            options).await {
+            Ok(signed_txn) => signed_txn,
+            Err(e) => {
+                self.release_write(checked);
+                return Err(e);
+            }
+        };
+
+        self.submit_signed_transaction(&signed_txn)
+            .await
+            .context("Failed to submit direct transfer token transaction")
+    }
+
+    /// Transfers to a receiver that opted in with `opt_in_direct_transfer`,
+    /// without their signature.
+    pub fn transfer_with_opt_in_payload(
+        &self,
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        property_version: u64,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection_name, name, Some(amount))?;
+
+        Ok(self.module_client.entry_function(
+            "transfer_with_opt_in",
             vec![],
-                vec![
+            vec![
                 bcs::to_bytes(&creator).unwrap(),
-                bcs::to_bytes(&collection_name).unwrap(),
-                bcs::to_bytes(&name).unwrap(),
+                bcs::to_bytes(collection_name).unwrap(),
+                bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
+                bcs::to_bytes(&receiver).unwrap(),
                 bcs::to_bytes(&amount).unwrap(),
             ],
-            options);
+        ))
+    }
 
-        Ok(self
-            .api_client
-            .submit(&signed_txn)
+    pub async fn transfer_with_opt_in(
+        &self,
+        account: &mut dyn TransactionSigner,
+        receiver: AccountAddress,
+        creator: AccountAddress,
+        collection_name: &str,
+        name: &str,
+        amount: u64,
+        property_version: Option<u64>,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let property_version = property_version.unwrap_or_default();
+        let payload = self.transfer_with_opt_in_payload(receiver, creator, collection_name, name, amount, property_version)?;
+
+        self.sign_and_submit(account, payload, options)
             .await
-            .context("Failed to submit direct transfer token transaction")?
-            .into_inner()
+            .context("Failed to submit transfer with opt-in transaction")
+    }
+
+    pub fn opt_in_direct_transfer_payload(&self, opt_in: bool) -> TransactionPayload {
+        self.module_client.entry_function(
+            "opt_in_direct_transfer",
+            vec![],
+            vec![bcs::to_bytes(&opt_in).unwrap()],
         )
     }
 
-    pub async fn list_token_for_swap(
+    pub async fn opt_in_direct_transfer(
+        &self,
+        account: &mut dyn TransactionSigner,
+        opt_in: bool,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.opt_in_direct_transfer_payload(opt_in);
+
+        self.sign_and_submit(account, payload, options)
+            .await
+            .context("Failed to submit opt in direct transfer transaction")
+    }
+
+    /// Whether `account` accepts `transfer_with_opt_in`. Accounts without a
+    /// token store have not opted in.
+    pub async fn is_direct_transfer_opted_in(&self, account: AccountAddress) -> Result<bool> {
+        let resource = self
+            .api_client
+            .get_account_resource(
+                account,
+                "0x3::token::TokenStore"
+            )
+            .await
+            .context("Error on getting account resource")?
+            .into_inner();
+
+        match resource {
+            Some(resource) => Ok(serde_json::from_str::<TokenStoreResources>(
+                &resource.data.to_string()
+            ).context("Error on parsing token store resources")?.direct_transfer),
+            None => Ok(false),
+        }
+    }
+
+    /// First phase of a direct transfer whose receiver signs elsewhere:
+    /// builds the transaction and adds the sender's signature. Export it
+    /// with `MultiAgentTransaction::to_json`, have the receiver `sign` it,
+    /// then hand it to `submit_multi_agent_transaction`.
+    pub async fn build_direct_transfer_token(
         &self,
-        account: &mut LocalAccount,
+        account: &mut dyn TransactionSigner,
+        receiver: AccountAddress,
         creator: AccountAddress,
-        collection: String,
+        collection_name: String,
         name: String,
         amount: u64,
-        min_coin_per_token: u64,
-        locked_until_secs: u64,
         property_version: Option<u64>,
         options: Option<TransactionOptions>,
-    ) -> Result<PendingTransaction> {
+    ) -> Result<MultiAgentTransaction> {
         let property_version = property_version.unwrap_or_default();
         let options = options.unwrap_or_default();
+        let payload = self.direct_transfer_token_payload(creator, &collection_name, &name, amount, property_version)?;
 
-        let signed_txn = self.token_transfer_module_client.build_signed_transaction(
-            account,
+        let checked = self.check_write_online(account.address(), &payload, &[receiver]).await?;
+        let built: Result<MultiAgentTransaction> = async {
+            let raw_txn = self.module_client
+                .build_online_raw_transaction(account, payload, &options)
+                .await?;
+
+            let mut txn = MultiAgentTransaction::new(raw_txn, vec![receiver]);
+            txn.sign(account).await?;
+            Ok(txn)
+        }
+        .await;
+        if built.is_err() {
+            self.release_write(checked);
+        }
+        built
+    }
+
+    pub async fn submit_multi_agent_transaction(
+        &self,
+        txn: MultiAgentTransaction,
+    ) -> Result<PendingTransaction> {
+        let signed_txn = txn.into_signed_transaction()?;
+
+        self.submit_signed_transaction(&signed_txn)
+            .await
+            .context("Failed to submit multi-agent transaction")
+    }
+
+    pub fn list_token_for_swap_payload(
+        &self,
+        creator: AccountAddress,
+        collection: &str,
+        name: &str,
+        amount: u64,
+        min_coin_per_token: u64,
+        locked_until_secs: u64,
+        property_version: u64,
+        coin_type: &str,
+    ) -> Result<TransactionPayload> {
+        validate_token_args(collection, name, Some(amount))?;
+
+        Ok(self.token_coin_swap_module_client.entry_function(
             "list_token_for_swap",
             vec![
-                TypeTag::from_str(&options.coin_type).unwrap()
+                TypeTag::from_str(coin_type).context("Invalid coin type")?
             ],
             vec![
                 bcs::to_bytes(&creator).unwrap(),
-                bcs::to_bytes(&collection).unwrap(),
-                bcs::to_bytes(&name).unwrap(),
+                bcs::to_bytes(collection).unwrap(),
+                bcs::to_bytes(name).unwrap(),
                 bcs::to_bytes(&property_version).unwrap(),
                 bcs::to_bytes(&amount).unwrap(),
                 bcs::to_bytes(&min_coin_per_token).unwrap(),
                 bcs::to_bytes(&locked_until_secs).unwrap(),
             ],
-            options);
+        ))
+    }
+
+    pub async fn list_token_for_swap(
+        &self,
+        account: &mut dyn TransactionSigner,
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+        min_coin_per_token: u64,
+        locked_until_secs: u64,
+        property_version: Option<u64>,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let property_version = property_version.unwrap_or_default();
+        let options = options.unwrap_or_default();
+        let payload = self.list_token_for_swap_payload(
+            creator,
+            &collection,
+            &name,
+            amount,
+            min_coin_per_token,
+            locked_until_secs,
+            property_version,
+            &options.coin_type,
+        )?;
+
+        self.sign_and_submit(account, payload, Some(options))
+            .await
+            .context("Failed to submit list token for swap transaction")
+    }
+
+    /// Builds a transaction for `payload` with an explicit sequence number
+    /// and expiration, to be signed elsewhere and handed back to
+    /// `submit_signed_transaction`. The signing policy and mainnet
+    /// confirmation run here, since the signer is out of our hands.
+    pub fn build_unsigned_transaction(
+        &self,
+        sender: AccountAddress,
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<UnsignedTransaction> {
+        let options = options.unwrap_or_default();
+
+        self.check_write(sender, &payload, &[])?;
+        Ok(UnsignedTransaction::new(self.module_client.build_raw_transaction(
+            payload,
+            sender,
+            sequence_number,
+            expiration_timestamp_secs,
+            &options,
+        )))
+    }
 
+    /// Builds a transaction from a k-of-n account for its key holders to
+    /// sign, then submit with `submit_signed_transaction`.
+    pub fn build_multi_ed25519_transaction(
+        &self,
+        account: &mut MultiEd25519Account,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<MultiEd25519Transaction> {
+        let options = options.unwrap_or_default();
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + options.timeout_sec;
+
+        self.check_write(account.address(), &payload, &[])?;
+        let unsigned = UnsignedTransaction::new(self.module_client.build_raw_transaction(
+            payload,
+            account.address(),
+            account.increment_sequence_number(),
+            expiration_timestamp_secs,
+            &options,
+        ));
+
+        Ok(MultiEd25519Transaction::new(unsigned, account.public_key().clone()))
+    }
+
+    pub async fn submit_signed_transaction(
+        &self,
+        signed_txn: &SignedTransaction,
+    ) -> Result<PendingTransaction> {
         Ok(self
             .api_client
-            .submit(&signed_txn)
+            .submit(signed_txn)
             .await
-            .context("Failed to submit list token for swap transaction")?
+            .context("Failed to submit transaction")?
             .into_inner()
         )
     }
 
+    /// Runs `payload` from `sender` against the latest state without
+    /// committing it, returning the node's simulated transactions. Nothing
+    /// is signed: the node requires an invalid signature for simulation.
+    pub async fn simulate_payload(
+        &self,
+        sender: AccountAddress,
+        public_key: &Ed25519PublicKey,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<serde_json::Value> {
+        let options = options.unwrap_or_default();
+        let sequence_number = self
+            .api_client
+            .get_account(sender)
+            .await
+            .with_context(|| format!("Failed to get account {}", sender.to_hex_literal()))?
+            .into_inner()
+            .sequence_number;
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + options.timeout_sec;
+
+        // Nothing is signed, so the signing policy does not run.
+        let raw_txn = self.module_client.build_raw_transaction(
+            payload,
+            sender,
+            sequence_number,
+            expiration_timestamp_secs,
+            &options,
+        );
+        let signature = Ed25519Signature::try_from(&[0u8; ED25519_SIGNATURE_LENGTH][..]).unwrap();
+        let signed_txn = SignedTransaction::new(raw_txn, public_key.clone(), signature);
+
+        let url = self
+            .api_client
+            .build_path("transactions/simulate")
+            .context("Failed to build simulate URL")?;
+        self.http_client
+            .post(url)
+            .header("Content-Type", "application/x.aptos.signed_transaction+bcs")
+            .body(bcs::to_bytes(&signed_txn).context("Failed to serialize transaction")?)
+            .send()
+            .await
+            .context("Failed to simulate transaction")?
+            .error_for_status()
+            .context("Failed to simulate transaction")?
+            .json()
+            .await
+            .context("Error on parsing simulation result")
+    }
+
+    pub(crate) async fn sign_payload(
+        &self,
+        account: &mut dyn TransactionSigner,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<SignedTransaction> {
+        let options = options.unwrap_or_default();
+
+        let checked = self.check_write_online(account.address(), &payload, &[]).await?;
+        let signed_txn = self.module_client.build_signed_transaction(
+            account,
+            payload,
+            options).await;
+        // A failed signing attempt must not use up rate limits either.
+        if signed_txn.is_err() {
+            self.release_write(checked);
+        }
+        signed_txn
+    }
+
+    /// Signs and submits a payload from one of the `_payload` methods.
+    pub async fn submit_payload(
+        &self,
+        account: &mut dyn TransactionSigner,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let function_name = payload_function_name(&payload);
+
+        self.sign_and_submit(account, payload, options)
+            .await
+            .with_context(|| format!("Failed to submit {} transaction", function_name))
+    }
+
+    async fn sign_and_submit(
+        &self,
+        account: &mut dyn TransactionSigner,
+        payload: TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let signed_txn = self.sign_payload(account, payload, options).await?;
+
+        self.submit_signed_transaction(&signed_txn).await
+    }
+
     pub async fn get_pending_claims_resources_for_account(
         &self,
         account: AccountAddress,
     ) -> Result<PendingClaimsResources> {
+        self.try_get_pending_claims_resources_for_account(account)
+            .await?
+            .context("No Pending Claims Found")
+    }
+
+    /// `None` if `account` never made an offer.
+    pub async fn try_get_pending_claims_resources_for_account(
+        &self,
+        account: AccountAddress,
+    ) -> Result<Option<PendingClaimsResources>> {
         let resource = self
             .api_client
             .get_account_resource(
@@ -510,14 +1205,17 @@ impl<'a> TokenClient<'a> {
             )
             .await
             .context("Error on getting account resource <0x3::token_transfers::PendingClaims>")?
-            .into_inner()
-            .context("No Pending Claims Found")?;
+            .into_inner();
+        let resource = match resource {
+            Some(resource) => resource,
+            None => return Ok(None),
+        };
 
         let data = serde_json::from_str::<PendingClaimsResources>(
             &resource.data.to_string()
         ).context("Error on parsing pending claims resource")?;
 
-        return Ok(data);
+        Ok(Some(data))
     }
 
     pub async fn get_token_offer_count(
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use aptos_sdk::types::account_address::AccountAddress;
+
+use crate::{
+    events::{get_events, Page},
+    types::{CollectionData, CollectionsResources, CreateCollectionEvent, CreateTokenDataEvent, TokenData},
+    TokenClient,
+};
+
+impl<'a> TokenClient<'a> {
+    async fn get_collections_resources(&self, creator: AccountAddress) -> Result<Option<CollectionsResources>> {
+        let resource = self
+            .api_client
+            .get_account_resource(
+                creator,
+                "0x3::token::Collections"
+            )
+            .await
+            .context("Error on getting account resource <0x3::token::Collections>")?
+            .into_inner();
+
+        resource
+            .map(|resource| {
+                serde_json::from_str::<CollectionsResources>(
+                    &resource.data.to_string()
+                ).context("Error on parsing account resources")
+            })
+            .transpose()
+    }
+
+    /// Collections created by `creator`, in creation order, read from its
+    /// `create_collection_events`. Start with a `cursor` of `None`.
+    pub async fn list_collections(
+        &self,
+        creator: AccountAddress,
+        cursor: Option<u64>,
+        limit: u16,
+    ) -> Result<Page<CollectionData>> {
+        let resources = match self.get_collections_resources(creator).await? {
+            Some(resources) => resources,
+            None => return Ok(Page { items: vec![], next_cursor: None }),
+        };
+        let start = cursor.unwrap_or(0);
+        let total = resources.create_collection_events.counter.0;
+        if start >= total {
+            return Ok(Page { items: vec![], next_cursor: None });
+        }
+
+        let events = get_events::<CreateCollectionEvent>(
+            self.api_client,
+            creator,
+            "0x3::token::Collections",
+            "create_collection_events",
+            start,
+            limit,
+        ).await?;
+
+        let mut items = Vec::with_capacity(events.len());
+        for event in &events {
+            let item = self.api_client.get_table_item(
+                resources.collection_data.handle,
+                "0x1::string::String",
+                "0x3::token::CollectionData",
+                &event.data.collection_name,
+            )
+            .await
+            .with_context(|| format!("Collection `{}` not found", event.data.collection_name))?;
+
+            items.push(serde_json::from_str::<CollectionData>(
+                &item.into_inner().to_string()
+            ).context("Error on parsing collection data")?);
+        }
+
+        let next = start + events.len() as u64;
+        Ok(Page {
+            items,
+            next_cursor: if events.is_empty() || next >= total { None } else { Some(next) },
+        })
+    }
+
+    /// Token data of `collection`, in creation order, read from the
+    /// creator's `create_token_data_events`. The events of all collections
+    /// share one handle, so a page scans `limit` events and may hold fewer
+    /// items than that.
+    pub async fn list_token_data(
+        &self,
+        creator: AccountAddress,
+        collection: &str,
+        cursor: Option<u64>,
+        limit: u16,
+    ) -> Result<Page<TokenData>> {
+        let resources = match self.get_collections_resources(creator).await? {
+            Some(resources) => resources,
+            None => return Ok(Page { items: vec![], next_cursor: None }),
+        };
+        let start = cursor.unwrap_or(0);
+        let total = resources.create_token_data_events.counter.0;
+        if start >= total {
+            return Ok(Page { items: vec![], next_cursor: None });
+        }
+
+        let events = get_events::<CreateTokenDataEvent>(
+            self.api_client,
+            creator,
+            "0x3::token::Collections",
+            "create_token_data_events",
+            start,
+            limit,
+        ).await?;
+
+        let mut items = Vec::new();
+        for event in events.iter().filter(|event| event.data.id.collection == collection) {
+            let item = self.api_client.get_table_item(
+                resources.token_data.handle,
+                "0x3::token::TokenDataId",
+                "0x3::token::TokenData",
+                &event.data.id,
+            )
+            .await
+            .with_context(|| format!("Token `{}` not found", event.data.id.name))?;
+
+            items.push(serde_json::from_str::<TokenData>(
+                &item.into_inner().to_string()
+            ).context("Error on parsing token data")?);
+        }
+
+        let next = start + events.len() as u64;
+        Ok(Page {
+            items,
+            next_cursor: if events.is_empty() || next >= total { None } else { Some(next) },
+        })
+    }
+}
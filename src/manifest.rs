@@ -0,0 +1,355 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{rest_client::aptos_api_types::HashValue, types::account_address::AccountAddress};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::get_all_events,
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    signer::TransactionSigner,
+    types::{CollectionData, CollectionsResources, MintTokenEvent, TokenData, TokenDataId, TransactionOptions},
+    TokenClient,
+};
+
+/// A collection and its tokens, as they should exist on chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Manifest {
+    pub collection: CreateCollectionRequest,
+    #[serde(default)]
+    pub tokens: Vec<CreateTokenRequest>,
+}
+
+impl Manifest {
+    /// Reads a YAML manifest from `.yaml`/`.yml` files, JSON otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let manifest: Manifest = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => serde_yaml::from_str(&content).context("Error on parsing manifest")?,
+            _ => serde_json::from_str(&content).context("Error on parsing manifest")?,
+        };
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.collection.validate()?;
+        let mut names = HashSet::new();
+        for token in &self.tokens {
+            if token.collection != self.collection.name {
+                bail!(
+                    "Token `{}` belongs to collection `{}`, not `{}`",
+                    token.name,
+                    token.collection,
+                    self.collection.name
+                );
+            }
+            if !names.insert(&token.name) {
+                bail!("Token `{}` is listed twice", token.name);
+            }
+            token.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum DeployAction {
+    CreateCollection(CreateCollectionRequest),
+    CreateToken(CreateTokenRequest),
+    /// Mints the part of the manifest's supply never minted before.
+    MintToken { name: String, amount: u64 },
+}
+
+impl DeployAction {
+    pub fn describe(&self) -> String {
+        match self {
+            DeployAction::CreateCollection(request) => format!("create collection `{}`", request.name),
+            DeployAction::CreateToken(request) => format!("create token `{}`", request.name),
+            DeployAction::MintToken { name, amount } => format!("mint {} of token `{}`", amount, name),
+        }
+    }
+}
+
+/// A difference the deploy cannot reconcile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeployConflict {
+    /// `collection` or the token name.
+    pub item: String,
+    pub field: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeployPlan {
+    pub ledger_version: u64,
+    pub actions: Vec<DeployAction>,
+    pub conflicts: Vec<DeployConflict>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeployReport {
+    /// Each executed action with its committed transaction hash.
+    pub executed: Vec<(String, HashValue)>,
+    pub conflicts: Vec<DeployConflict>,
+}
+
+fn conflict(item: &str, field: &str, mutable: bool) -> DeployConflict {
+    let reason = if mutable {
+        // `0x3::token` exposes these mutations as public functions, which
+        // only a Move script can call; deploy does not send scripts yet.
+        format!("`{}` is mutable, but changing it is not implemented yet", field)
+    } else {
+        format!("`{}` is immutable on chain", field)
+    };
+    DeployConflict {
+        item: item.to_string(),
+        field: field.to_string(),
+        reason,
+    }
+}
+
+fn diff_collection(manifest: &CreateCollectionRequest, data: &CollectionData, conflicts: &mut Vec<DeployConflict>) {
+    let mutability = &data.mutability_config;
+    if manifest.description != data.description {
+        conflicts.push(conflict("collection", "description", mutability.description));
+    }
+    if manifest.uri != data.uri {
+        conflicts.push(conflict("collection", "uri", mutability.uri));
+    }
+    if manifest.maximum != data.maximum.0 {
+        conflicts.push(conflict("collection", "maximum", mutability.maximum));
+    }
+    if manifest.mutability.description_mutable != mutability.description
+        || manifest.mutability.uri_mutable != mutability.uri
+        || manifest.mutability.supply_mutable != mutability.maximum
+    {
+        conflicts.push(conflict("collection", "mutability", false));
+    }
+}
+
+/// Properties keyed by name, with values hex encoded as the API returns them.
+fn manifest_properties(request: &CreateTokenRequest) -> BTreeMap<String, (String, String)> {
+    let properties = &request.properties;
+    properties
+        .keys
+        .iter()
+        .zip(&properties.values)
+        .zip(&properties.types)
+        .map(|((key, value), type_)| (key.clone(), (format!("0x{}", hex::encode(value)), type_.clone())))
+        .collect()
+}
+
+fn diff_token(
+    creator: AccountAddress,
+    manifest: &CreateTokenRequest,
+    data: &TokenData,
+    minted: u64,
+    actions: &mut Vec<DeployAction>,
+    conflicts: &mut Vec<DeployConflict>,
+) {
+    let name = manifest.name.as_str();
+    let mutability = &data.mutability_config;
+    if manifest.description != data.description {
+        conflicts.push(conflict(name, "description", mutability.description));
+    }
+    if manifest.uri != data.uri {
+        conflicts.push(conflict(name, "uri", mutability.uri));
+    }
+    if Some(manifest.maximum) != data.maximum.as_ref().map(|maximum| maximum.0) {
+        conflicts.push(conflict(name, "maximum", mutability.maximum));
+    }
+    if manifest.royalty_payee.unwrap_or(creator) != data.royalty.payee_address
+        || manifest.royalty.numerator != data.royalty.royalty_points_numerator.0
+        || manifest.royalty.denominator != data.royalty.royalty_points_denominator.0
+    {
+        conflicts.push(conflict(name, "royalty", mutability.royalty));
+    }
+    if manifest.mutability.to_vec()
+        != vec![
+            mutability.maximum,
+            mutability.uri,
+            mutability.royalty,
+            mutability.description,
+            mutability.properties,
+        ]
+    {
+        conflicts.push(conflict(name, "mutability", false));
+    }
+
+    let chain_properties: BTreeMap<_, _> = data
+        .default_properties
+        .map
+        .data
+        .iter()
+        .map(|entry| (entry.key.clone(), (entry.value.value.clone(), entry.value.type_.clone())))
+        .collect();
+    if manifest_properties(manifest) != chain_properties {
+        // `mutate_token_properties` rewrites one owner's token under a new
+        // property version; the token data's defaults stay unchanged.
+        conflicts.push(DeployConflict {
+            item: name.to_string(),
+            field: "properties".to_string(),
+            reason: if mutability.properties {
+                "default properties are mutable, but changing them is not implemented yet".to_string()
+            } else {
+                "`properties` is immutable on chain".to_string()
+            },
+        });
+    }
+
+    // Only tokens never minted are minted: burned tokens stay burned.
+    // Tokens in circulation are only tracked for tokens with a maximum.
+    let supply = data.supply.0;
+    if manifest.supply > minted {
+        actions.push(DeployAction::MintToken {
+            name: manifest.name.clone(),
+            amount: manifest.supply - minted,
+        });
+    } else if manifest.maximum > 0 && manifest.supply < supply {
+        conflicts.push(DeployConflict {
+            item: name.to_string(),
+            field: "supply".to_string(),
+            reason: format!("{} in circulation, more than the manifest's {}; burn them to reconcile", supply, manifest.supply),
+        });
+    }
+}
+
+impl<'a> TokenClient<'a> {
+    /// Compares `manifest` with what `creator` has on chain at the latest
+    /// ledger version.
+    pub async fn plan_deploy(&self, creator: AccountAddress, manifest: &Manifest) -> Result<DeployPlan> {
+        manifest.validate()?;
+        let ledger_version = self.get_ledger_version().await?;
+
+        let mut actions = Vec::new();
+        let mut conflicts = Vec::new();
+
+        let collections = match self
+            .get_account_resource_at_version(creator, "0x3::token::Collections", ledger_version)
+            .await?
+        {
+            Some(resource) => Some(
+                serde_json::from_value::<CollectionsResources>(resource)
+                    .context("Error on parsing account resources")?,
+            ),
+            None => None,
+        };
+
+        let collection_data = match &collections {
+            Some(collections) => self
+                .try_get_table_item_at_version(
+                    collections.collection_data.handle,
+                    "0x1::string::String",
+                    "0x3::token::CollectionData",
+                    &manifest.collection.name,
+                    ledger_version,
+                )
+                .await?
+                .map(|item| serde_json::from_value::<CollectionData>(item).context("Error on parsing collection data"))
+                .transpose()?,
+            None => None,
+        };
+        match &collection_data {
+            Some(data) => diff_collection(&manifest.collection, data, &mut conflicts),
+            None => actions.push(DeployAction::CreateCollection(manifest.collection.clone())),
+        }
+
+        // Amounts ever minted by token data, including the initial balance
+        // of `create_token_script`.
+        let mut minted = HashMap::<TokenDataId, u64>::new();
+        if let (Some(collections), Some(_)) = (&collections, &collection_data) {
+            let mints = get_all_events::<MintTokenEvent>(
+                self.api_client,
+                creator,
+                "0x3::token::Collections",
+                "mint_token_events",
+                collections.mint_token_events.counter.0,
+            )
+            .await?;
+            for mint in mints.into_iter().filter(|mint| mint.version <= ledger_version) {
+                *minted.entry(mint.data.id).or_default() += mint.data.amount.0;
+            }
+        }
+
+        for token in &manifest.tokens {
+            let token_data_id = TokenDataId {
+                creator,
+                collection: token.collection.clone(),
+                name: token.name.clone(),
+            };
+            let token_data = match (&collections, &collection_data) {
+                (Some(collections), Some(_)) => {
+                    self.try_get_table_item_at_version(
+                        collections.token_data.handle,
+                        "0x3::token::TokenDataId",
+                        "0x3::token::TokenData",
+                        &token_data_id,
+                        ledger_version,
+                    )
+                    .await?
+                    .map(|item| serde_json::from_value::<TokenData>(item).context("Error on parsing token data"))
+                    .transpose()?
+                }
+                _ => None,
+            };
+
+            match token_data {
+                Some(data) => {
+                    let minted = minted.get(&token_data_id).copied().unwrap_or(0);
+                    diff_token(creator, token, &data, minted, &mut actions, &mut conflicts)
+                }
+                None => actions.push(DeployAction::CreateToken(token.clone())),
+            }
+        }
+
+        Ok(DeployPlan { ledger_version, actions, conflicts })
+    }
+
+    /// Plans, then runs every action in order, waiting for each to commit.
+    ///
+    /// Items already on chain are skipped, so a deploy that stopped midway
+    /// can simply be run again. A transaction left pending by the stopped
+    /// run shares its sequence number with the first one of the new run,
+    /// provided `creator` was loaded from the chain, so only one of them
+    /// commits.
+    pub async fn deploy(
+        &self,
+        creator: &mut dyn TransactionSigner,
+        manifest: &Manifest,
+        options: Option<TransactionOptions>,
+    ) -> Result<DeployReport> {
+        let plan = self.plan_deploy(creator.address(), manifest).await?;
+
+        let mut executed = Vec::new();
+        for action in plan.actions {
+            let description = action.describe();
+            let pending = match &action {
+                DeployAction::CreateCollection(request) => {
+                    self.create_collection_script(creator, request, options.clone()).await?
+                }
+                DeployAction::CreateToken(request) => self.create_token(creator, request, options.clone()).await?,
+                DeployAction::MintToken { name, amount } => {
+                    self.mint_token(creator, &manifest.collection.name, name, *amount, options.clone())
+                        .await?
+                }
+            };
+            self.api_client
+                .wait_for_transaction(&pending)
+                .await
+                .with_context(|| format!("Failed to {}", description))?;
+            executed.push((description, pending.hash));
+        }
+
+        Ok(DeployReport {
+            executed,
+            conflicts: plan.conflicts,
+        })
+    }
+}
@@ -1,16 +1,19 @@
 use std::time::{ SystemTime, UNIX_EPOCH};
+use anyhow::Result;
 use aptos_sdk::{
     types::{
-        LocalAccount,
         chain_id::ChainId,
-        transaction::{TransactionPayload, EntryFunction, SignedTransaction},
+        transaction::{TransactionPayload, EntryFunction, RawTransaction, SignedTransaction},
         account_address::AccountAddress,
     },
     transaction_builder::TransactionBuilder,
     move_types::{identifier::Identifier, language_storage::{ModuleId, TypeTag}}
 };
 
-use crate::types::TransactionOptions;
+use crate::{
+    signer::{sign_multi_agent_transaction, sign_transaction, TransactionSigner},
+    types::TransactionOptions,
+};
 
 #[derive(Clone, Debug)]
 pub struct ModuleClient {
@@ -33,66 +36,75 @@ impl ModuleClient {
         }
     }
 
-    pub fn build_signed_transaction(
+    pub fn entry_function(
         &self,
-        account: &mut LocalAccount, 
         function_name: &str,
         ty_args: Vec<TypeTag>,
         args: Vec<Vec<u8>>,
-        tx_opts: TransactionOptions
-    ) -> SignedTransaction {
-        let transaction_builder = TransactionBuilder::new(
-            TransactionPayload::EntryFunction(EntryFunction::new(
-                self.module.clone(),
-                Identifier::new(function_name).unwrap(),
-                ty_args,
-                args,
-            )),
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-                + tx_opts.timeout_sec,
-            self.chain_id,
-        )
-        .sender(account.address())
-        .sequence_number(account.sequence_number())
-        .max_gas_amount(tx_opts.max_gas_amount)
-        .gas_unit_price(tx_opts.gas_unit_price);
+    ) -> TransactionPayload {
+        TransactionPayload::EntryFunction(EntryFunction::new(
+            self.module.clone(),
+            Identifier::new(function_name).unwrap(),
+            ty_args,
+            args,
+        ))
+    }
 
-        account.sign_with_transaction_builder(transaction_builder)
+    pub fn build_raw_transaction(
+        &self,
+        payload: TransactionPayload,
+        sender: AccountAddress,
+        sequence_number: u64,
+        expiration_timestamp_secs: u64,
+        tx_opts: &TransactionOptions
+    ) -> RawTransaction {
+        TransactionBuilder::new(payload, expiration_timestamp_secs, self.chain_id)
+            .sender(sender)
+            .sequence_number(sequence_number)
+            .max_gas_amount(tx_opts.max_gas_amount)
+            .gas_unit_price(tx_opts.gas_unit_price)
+            .build()
     }
 
-    pub fn build_multisigned_transaction(
+    pub async fn build_online_raw_transaction(
         &self,
-        account: &mut LocalAccount,
-        other_accounts: Vec<&LocalAccount>,
-        function_name: &str,
-        ty_args: Vec<TypeTag>,
-        args: Vec<Vec<u8>>,
+        account: &mut dyn TransactionSigner,
+        payload: TransactionPayload,
+        tx_opts: &TransactionOptions
+    ) -> Result<RawTransaction> {
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + tx_opts.timeout_sec;
+
+        Ok(self.build_raw_transaction(
+            payload,
+            account.address(),
+            account.next_sequence_number().await?,
+            expiration_timestamp_secs,
+            tx_opts,
+        ))
+    }
+
+    pub async fn build_signed_transaction(
+        &self,
+        account: &mut dyn TransactionSigner,
+        payload: TransactionPayload,
         tx_opts: TransactionOptions
-    ) -> SignedTransaction {
-        
-        let transaction_builder = TransactionBuilder::new(
-            TransactionPayload::EntryFunction(EntryFunction::new(
-                self.module.clone(),
-                Identifier::new(function_name).unwrap(),
-                ty_args,
-                args,
-            )),
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-                + tx_opts.timeout_sec,
-            self.chain_id,
-        )
-        .sender(account.address())
-        .sequence_number(account.sequence_number())
-        .max_gas_amount(tx_opts.max_gas_amount)
-        .gas_unit_price(tx_opts.gas_unit_price);
+    ) -> Result<SignedTransaction> {
+        let raw_txn = self.build_online_raw_transaction(account, payload, &tx_opts).await?;
+        sign_transaction(account, raw_txn).await
+    }
 
-        account.sign_multi_agent_with_transaction_builder(other_accounts, transaction_builder)
+    pub async fn build_multisigned_transaction(
+        &self,
+        account: &mut dyn TransactionSigner,
+        other_accounts: Vec<&dyn TransactionSigner>,
+        payload: TransactionPayload,
+        tx_opts: TransactionOptions
+    ) -> Result<SignedTransaction> {
+        let raw_txn = self.build_online_raw_transaction(account, payload, &tx_opts).await?;
+        sign_multi_agent_transaction(account, &other_accounts, raw_txn).await
     }
 }
-
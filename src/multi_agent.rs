@@ -0,0 +1,201 @@
+use anyhow::{bail, ensure, Context, Result};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        Signature,
+    },
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+            SignedTransaction,
+        },
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{offline::payload_function_name, signer::TransactionSigner};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PartialSignature {
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+}
+
+/// A multi-agent transaction collecting signatures from its sender and
+/// secondary signers, possibly in different processes. It serializes to
+/// JSON so it can be passed between parties; every signature added is
+/// checked against the transaction before it is accepted.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MultiAgentTransaction {
+    raw_txn: RawTransaction,
+    secondary_signer_addresses: Vec<AccountAddress>,
+    sender_signature: Option<PartialSignature>,
+    secondary_signatures: Vec<Option<PartialSignature>>,
+}
+
+impl MultiAgentTransaction {
+    pub fn new(raw_txn: RawTransaction, secondary_signer_addresses: Vec<AccountAddress>) -> Self {
+        let secondary_signatures = vec![None; secondary_signer_addresses.len()];
+        Self {
+            raw_txn,
+            secondary_signer_addresses,
+            sender_signature: None,
+            secondary_signatures,
+        }
+    }
+
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.raw_txn
+    }
+
+    pub fn secondary_signer_addresses(&self) -> &[AccountAddress] {
+        &self.secondary_signer_addresses
+    }
+
+    pub fn summary(&self) -> String {
+        let secondary: Vec<String> = self
+            .secondary_signer_addresses
+            .iter()
+            .map(|address| address.to_hex_literal())
+            .collect();
+        format!(
+            "{} from {} (sequence number {}) with secondary signers [{}], expires at {} (unix), chain ID {}",
+            payload_function_name(self.raw_txn.payload()),
+            self.raw_txn.sender().to_hex_literal(),
+            self.raw_txn.sequence_number(),
+            secondary.join(", "),
+            self.raw_txn.expiration_timestamp_secs(),
+            self.raw_txn.chain_id(),
+        )
+    }
+
+    /// The message every party signs.
+    pub fn signing_message(&self) -> RawTransactionWithData {
+        RawTransactionWithData::new_multi_agent(
+            self.raw_txn.clone(),
+            self.secondary_signer_addresses.clone(),
+        )
+    }
+
+    /// Adds a signature produced elsewhere, e.g. by a wallet, for `address`.
+    pub fn add_signature(
+        &mut self,
+        address: AccountAddress,
+        public_key: Ed25519PublicKey,
+        signature: Ed25519Signature,
+    ) -> Result<()> {
+        signature
+            .verify(&self.signing_message(), &public_key)
+            .with_context(|| format!("Invalid signature for {}", address.to_hex_literal()))?;
+        let partial = PartialSignature { public_key, signature };
+
+        if address == self.raw_txn.sender() {
+            self.sender_signature = Some(partial);
+            return Ok(());
+        }
+        let slot = self
+            .secondary_signer_addresses
+            .iter()
+            .position(|secondary| *secondary == address)
+            .and_then(|index| self.secondary_signatures.get_mut(index));
+        match slot {
+            Some(slot) => {
+                *slot = Some(partial);
+                Ok(())
+            }
+            None => bail!(
+                "{} is not a signer of this transaction",
+                address.to_hex_literal()
+            ),
+        }
+    }
+
+    pub async fn sign(&mut self, signer: &dyn TransactionSigner) -> Result<()> {
+        let signature = signer
+            .sign_multi_agent_transaction(&self.signing_message())
+            .await?;
+        self.add_signature(signer.address(), signer.public_key(), signature)
+    }
+
+    pub fn missing_signers(&self) -> Vec<AccountAddress> {
+        let mut missing = Vec::new();
+        if self.sender_signature.is_none() {
+            missing.push(self.raw_txn.sender());
+        }
+        for (address, signature) in self
+            .secondary_signer_addresses
+            .iter()
+            .zip(self.secondary_signatures.iter())
+        {
+            if signature.is_none() {
+                missing.push(*address);
+            }
+        }
+        missing
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.missing_signers().is_empty()
+    }
+
+    pub fn into_signed_transaction(self) -> Result<SignedTransaction> {
+        let missing = self.missing_signers();
+        ensure!(
+            missing.is_empty(),
+            "Missing signatures from {:?}",
+            missing.iter().map(|a| a.to_hex_literal()).collect::<Vec<_>>()
+        );
+
+        let sender = self.sender_signature.unwrap();
+        let secondary_signers = self
+            .secondary_signatures
+            .into_iter()
+            .map(|partial| {
+                let partial = partial.unwrap();
+                AccountAuthenticator::ed25519(partial.public_key, partial.signature)
+            })
+            .collect();
+
+        Ok(SignedTransaction::new_multi_agent(
+            self.raw_txn,
+            AccountAuthenticator::ed25519(sender.public_key, sender.signature),
+            self.secondary_signer_addresses,
+            secondary_signers,
+        ))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize multi-agent transaction")
+    }
+
+    /// Parses a transaction from another party, checking that it has a
+    /// signature slot per secondary signer and that every signature in it
+    /// is valid.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let txn: Self = serde_json::from_str(json).context("Error on parsing multi-agent transaction")?;
+        ensure!(
+            txn.secondary_signatures.len() == txn.secondary_signer_addresses.len(),
+            "Multi-agent transaction has {} secondary signatures for {} secondary signers",
+            txn.secondary_signatures.len(),
+            txn.secondary_signer_addresses.len()
+        );
+
+        let message = txn.signing_message();
+        let signatures = std::iter::once((txn.raw_txn.sender(), &txn.sender_signature)).chain(
+            txn.secondary_signer_addresses
+                .iter()
+                .copied()
+                .zip(txn.secondary_signatures.iter()),
+        );
+        for (address, partial) in signatures {
+            if let Some(partial) = partial {
+                partial
+                    .signature
+                    .verify(&message, &partial.public_key)
+                    .with_context(|| format!("Invalid signature for {}", address.to_hex_literal()))?;
+            }
+        }
+        Ok(txn)
+    }
+}
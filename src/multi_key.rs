@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure, Context, Result};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+        Signature,
+    },
+    types::{
+        account_address::AccountAddress,
+        transaction::{authenticator::AuthenticationKey, RawTransaction, SignedTransaction},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{offline::UnsignedTransaction, signer::TransactionSigner};
+
+/// A k-of-n MultiEd25519 account. The key holders sign independently; see
+/// `MultiEd25519Transaction`.
+#[derive(Clone, Debug)]
+pub struct MultiEd25519Account {
+    public_key: MultiEd25519PublicKey,
+    address: AccountAddress,
+    sequence_number: u64,
+}
+
+impl MultiEd25519Account {
+    pub fn new(public_keys: Vec<Ed25519PublicKey>, threshold: u8, sequence_number: u64) -> Result<Self> {
+        let public_key = MultiEd25519PublicKey::new(public_keys, threshold)
+            .context("Invalid MultiEd25519 public keys or threshold")?;
+        let address = AuthenticationKey::multi_ed25519(&public_key).derived_address();
+        Ok(Self { public_key, address, sequence_number })
+    }
+
+    /// For accounts whose authentication key was rotated to this key set.
+    pub fn with_address(mut self, address: AccountAddress) -> Self {
+        self.address = address;
+        self
+    }
+
+    pub fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn increment_sequence_number(&mut self) -> u64 {
+        let sequence_number = self.sequence_number;
+        self.sequence_number += 1;
+        sequence_number
+    }
+}
+
+/// A transaction from a MultiEd25519 account collecting partial
+/// signatures. Each one is verified against its key when added, and the
+/// combined signature again before it is turned into a `SignedTransaction`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultiEd25519Transaction {
+    raw_txn: RawTransaction,
+    public_key: MultiEd25519PublicKey,
+    signatures: BTreeMap<u8, Ed25519Signature>,
+}
+
+impl MultiEd25519Transaction {
+    pub fn new(unsigned: UnsignedTransaction, public_key: MultiEd25519PublicKey) -> Self {
+        Self {
+            raw_txn: unsigned.into_raw_transaction(),
+            public_key,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.raw_txn
+    }
+
+    pub fn threshold(&self) -> u8 {
+        *self.public_key.threshold()
+    }
+
+    pub fn signature_count(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= self.threshold() as usize
+    }
+
+    /// Adds the signature of the key at `index` in the account's key list.
+    pub fn add_signature(&mut self, index: u8, signature: Ed25519Signature) -> Result<()> {
+        let public_key = match self.public_key.public_keys().get(index as usize) {
+            Some(public_key) => public_key,
+            None => bail!("Key index {} is out of range", index),
+        };
+        signature
+            .verify(&self.raw_txn, public_key)
+            .with_context(|| format!("Invalid signature for key {}", index))?;
+        self.signatures.insert(index, signature);
+        Ok(())
+    }
+
+    pub async fn sign(&mut self, signer: &dyn TransactionSigner) -> Result<()> {
+        let public_key = signer.public_key();
+        let index = self
+            .public_key
+            .public_keys()
+            .iter()
+            .position(|key| *key == public_key)
+            .context("Signer's key is not part of this MultiEd25519 account")?;
+        let signature = signer.sign_raw_transaction(&self.raw_txn).await?;
+        self.add_signature(index as u8, signature)
+    }
+
+    pub fn into_signed_transaction(self) -> Result<SignedTransaction> {
+        ensure!(
+            self.is_complete(),
+            "{} of {} required signatures collected",
+            self.signatures.len(),
+            self.threshold()
+        );
+
+        let signature = MultiEd25519Signature::new(
+            self.signatures
+                .into_iter()
+                .map(|(index, signature)| (signature, index))
+                .collect(),
+        )
+        .context("Failed to combine signatures")?;
+        signature
+            .verify(&self.raw_txn, &self.public_key)
+            .context("Combined MultiEd25519 signature does not verify")?;
+
+        Ok(SignedTransaction::new_multisig(self.raw_txn, self.public_key, signature))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize MultiEd25519 transaction")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Error on parsing MultiEd25519 transaction")
+    }
+}
@@ -0,0 +1,298 @@
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{
+    bcs,
+    rest_client::{aptos_api_types::U64, PendingTransaction},
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    module_client::ModuleClient,
+    offline::{decode_hex, payload_function_name},
+    operation::TokenOperation,
+    signer::TransactionSigner,
+    types::{Handle, MoveOption, SimpleMap, TransactionOptions},
+    TokenClient,
+};
+
+pub(crate) fn multisig_account_module_client(chain_id: u8) -> ModuleClient {
+    ModuleClient::new(chain_id, AccountAddress::ONE, "multisig_account")
+}
+
+/// BCS of `0x1::multisig_account::MultisigTransactionPayload`, whose only
+/// variant wraps an entry function.
+pub fn multisig_payload_bytes(payload: &TransactionPayload) -> Result<Vec<u8>> {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => {
+            let mut bytes = vec![0u8];
+            bytes.extend(bcs::to_bytes(entry_function).context("Failed to serialize entry function")?);
+            Ok(bytes)
+        }
+        _ => bail!(
+            "Only entry functions can be proposed to a multisig account, got {}",
+            payload_function_name(payload)
+        ),
+    }
+}
+
+pub fn decode_multisig_payload(bytes: &[u8]) -> Result<EntryFunction> {
+    match bytes.split_first() {
+        Some((0, entry_function)) => {
+            bcs::from_bytes(entry_function).context("Error on parsing multisig entry function payload")
+        }
+        _ => bail!("Unknown multisig transaction payload"),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MultisigAccountResource {
+    pub owners: Vec<AccountAddress>,
+    pub num_signatures_required: U64,
+    pub last_executed_sequence_number: U64,
+    pub next_sequence_number: U64,
+    pub transactions: Handle,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MultisigTransactionResource {
+    pub payload: MoveOption<String>,
+    pub payload_hash: MoveOption<String>,
+    pub votes: SimpleMap<AccountAddress, bool>,
+    pub creator: AccountAddress,
+    pub creation_time_secs: U64,
+}
+
+#[derive(Debug)]
+pub struct MultisigProposal {
+    pub sequence_number: u64,
+    pub creator: AccountAddress,
+    pub creation_time_secs: u64,
+    pub approvals: Vec<AccountAddress>,
+    pub rejections: Vec<AccountAddress>,
+    /// `None` when only the payload hash was stored on chain.
+    pub entry_function: Option<EntryFunction>,
+}
+
+impl MultisigProposal {
+    pub fn function_name(&self) -> Option<String> {
+        self.entry_function
+            .as_ref()
+            .map(|entry_function| payload_function_name(&TransactionPayload::EntryFunction(entry_function.clone())))
+    }
+}
+
+impl<'a> TokenClient<'a> {
+    pub fn propose_multisig_transaction_payload(
+        &self,
+        multisig_account: AccountAddress,
+        payload: &TransactionPayload,
+    ) -> Result<TransactionPayload> {
+        Ok(self.multisig_account_module_client.entry_function(
+            "create_transaction",
+            vec![],
+            vec![
+                bcs::to_bytes(&multisig_account).unwrap(),
+                bcs::to_bytes(&multisig_payload_bytes(payload)?).unwrap(),
+            ],
+        ))
+    }
+
+    /// Stores `payload`, e.g. from `create_token_payload`, as a proposal of
+    /// `multisig_account`. `owner` must be one of its owners.
+    pub async fn propose_multisig_transaction(
+        &self,
+        owner: &mut dyn TransactionSigner,
+        multisig_account: AccountAddress,
+        payload: &TransactionPayload,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let proposal = self.propose_multisig_transaction_payload(multisig_account, payload)?;
+
+        self.sign_and_submit(owner, proposal, options)
+            .await
+            .context("Failed to submit multisig proposal transaction")
+    }
+
+    pub fn approve_multisig_transaction_payload(
+        &self,
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+    ) -> TransactionPayload {
+        self.vote_multisig_transaction_payload("approve_transaction", multisig_account, sequence_number)
+    }
+
+    pub async fn approve_multisig_transaction(
+        &self,
+        owner: &mut dyn TransactionSigner,
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.approve_multisig_transaction_payload(multisig_account, sequence_number);
+
+        self.sign_and_submit(owner, payload, options)
+            .await
+            .context("Failed to submit multisig approve transaction")
+    }
+
+    pub fn reject_multisig_transaction_payload(
+        &self,
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+    ) -> TransactionPayload {
+        self.vote_multisig_transaction_payload("reject_transaction", multisig_account, sequence_number)
+    }
+
+    pub async fn reject_multisig_transaction(
+        &self,
+        owner: &mut dyn TransactionSigner,
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.reject_multisig_transaction_payload(multisig_account, sequence_number);
+
+        self.sign_and_submit(owner, payload, options)
+            .await
+            .context("Failed to submit multisig reject transaction")
+    }
+
+    /// Removes the oldest pending proposal once it has enough rejections.
+    ///
+    /// Executing an approved proposal needs the multisig transaction
+    /// payload type, which the pinned `aptos-sdk` revision does not have.
+    pub async fn execute_rejected_multisig_transaction(
+        &self,
+        owner: &mut dyn TransactionSigner,
+        multisig_account: AccountAddress,
+        options: Option<TransactionOptions>,
+    ) -> Result<PendingTransaction> {
+        let payload = self.multisig_account_module_client.entry_function(
+            "execute_rejected_transaction",
+            vec![],
+            vec![bcs::to_bytes(&multisig_account).unwrap()],
+        );
+
+        self.sign_and_submit(owner, payload, options)
+            .await
+            .context("Failed to submit multisig execute rejected transaction")
+    }
+
+    fn vote_multisig_transaction_payload(
+        &self,
+        function_name: &str,
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+    ) -> TransactionPayload {
+        self.multisig_account_module_client.entry_function(
+            function_name,
+            vec![],
+            vec![
+                bcs::to_bytes(&multisig_account).unwrap(),
+                bcs::to_bytes(&sequence_number).unwrap(),
+            ],
+        )
+    }
+
+    /// Fills in the proposal a multisig vote is about, so the signing
+    /// policy checks what an approval runs. It stays `None` when only the
+    /// payload hash is stored on chain.
+    pub(crate) async fn resolve_multisig_vote(&self, operation: TokenOperation) -> Result<TokenOperation> {
+        match operation {
+            TokenOperation::MultisigVote { multisig_account, sequence_number, approve, proposal: None } => {
+                let transactions = self.get_multisig_account_resource(multisig_account).await?.transactions;
+                let proposal = self
+                    .get_multisig_proposal(transactions.handle, sequence_number)
+                    .await?
+                    .entry_function
+                    .map(|entry_function| {
+                        TokenOperation::from_payload(&TransactionPayload::EntryFunction(entry_function), &[])
+                    })
+                    .transpose()?
+                    .map(Box::new);
+                Ok(TokenOperation::MultisigVote { multisig_account, sequence_number, approve, proposal })
+            }
+            operation => Ok(operation),
+        }
+    }
+
+    pub async fn get_multisig_account_resource(
+        &self,
+        multisig_account: AccountAddress,
+    ) -> Result<MultisigAccountResource> {
+        let resource = self
+            .api_client
+            .get_account_resource(
+                multisig_account,
+                "0x1::multisig_account::MultisigAccount"
+            )
+            .await
+            .context("Error on getting account resource <0x1::multisig_account::MultisigAccount>")?
+            .into_inner()
+            .context("Not a multisig account")?;
+
+        serde_json::from_str::<MultisigAccountResource>(
+            &resource.data.to_string()
+        ).context("Error on parsing multisig account resource")
+    }
+
+    /// Every proposal not yet executed or removed, oldest first.
+    pub async fn get_pending_multisig_proposals(
+        &self,
+        multisig_account: AccountAddress,
+    ) -> Result<Vec<MultisigProposal>> {
+        let account = self.get_multisig_account_resource(multisig_account).await?;
+
+        let mut proposals = Vec::new();
+        for sequence_number in (account.last_executed_sequence_number.0 + 1)..account.next_sequence_number.0 {
+            proposals.push(
+                self.get_multisig_proposal(account.transactions.handle, sequence_number)
+                    .await?,
+            );
+        }
+
+        Ok(proposals)
+    }
+
+    /// Reads a proposal from the `transactions` table of a multisig account.
+    async fn get_multisig_proposal(
+        &self,
+        transactions: AccountAddress,
+        sequence_number: u64,
+    ) -> Result<MultisigProposal> {
+        let item = self.api_client.get_table_item(
+            transactions,
+            "u64",
+            "0x1::multisig_account::MultisigTransaction",
+            sequence_number.to_string(),
+        )
+        .await
+        .with_context(|| format!("Multisig proposal {} not found", sequence_number))?;
+
+        let transaction = serde_json::from_str::<MultisigTransactionResource>(
+            &item.into_inner().to_string()
+        ).context("Error on parsing multisig transaction")?;
+
+        let entry_function = match transaction.payload.vec.first() {
+            Some(payload) => Some(decode_multisig_payload(&decode_hex(payload)?)?),
+            None => None,
+        };
+        let (approvals, rejections): (Vec<_>, Vec<_>) = transaction
+            .votes
+            .data
+            .iter()
+            .partition(|vote| vote.value);
+
+        Ok(MultisigProposal {
+            sequence_number,
+            creator: transaction.creator,
+            creation_time_secs: transaction.creation_time_secs.0,
+            approvals: approvals.into_iter().map(|vote| vote.key).collect(),
+            rejections: rejections.into_iter().map(|vote| vote.key).collect(),
+            entry_function,
+        })
+    }
+}
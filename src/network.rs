@@ -0,0 +1,149 @@
+use std::{fmt, str::FromStr, sync::Arc};
+
+use anyhow::{bail, Result};
+use url::Url;
+
+pub const MAINNET_CHAIN_ID: u8 = 1;
+pub const TESTNET_CHAIN_ID: u8 = 2;
+pub const LOCAL_CHAIN_ID: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Local,
+    Custom {
+        node_url: Url,
+        faucet_url: Option<Url>,
+        chain_id: Option<u8>,
+    },
+}
+
+impl Network {
+    /// The chain ID the fullnode is expected to report. Devnet is reset
+    /// regularly and gets a new chain ID each time, so it has none.
+    pub fn chain_id(&self) -> Option<u8> {
+        match self {
+            Network::Mainnet => Some(MAINNET_CHAIN_ID),
+            Network::Testnet => Some(TESTNET_CHAIN_ID),
+            Network::Devnet => None,
+            Network::Local => Some(LOCAL_CHAIN_ID),
+            Network::Custom { chain_id, .. } => *chain_id,
+        }
+    }
+
+    pub fn node_url(&self) -> Url {
+        match self {
+            Network::Mainnet => Url::parse("https://fullnode.mainnet.aptoslabs.com").unwrap(),
+            Network::Testnet => Url::parse("https://fullnode.testnet.aptoslabs.com").unwrap(),
+            Network::Devnet => Url::parse("https://fullnode.devnet.aptoslabs.com").unwrap(),
+            Network::Local => Url::parse("http://127.0.0.1:8080").unwrap(),
+            Network::Custom { node_url, .. } => node_url.clone(),
+        }
+    }
+
+    pub fn faucet_url(&self) -> Option<Url> {
+        match self {
+            Network::Mainnet => None,
+            Network::Testnet => Some(Url::parse("https://faucet.testnet.aptoslabs.com").unwrap()),
+            Network::Devnet => Some(Url::parse("https://faucet.devnet.aptoslabs.com").unwrap()),
+            Network::Local => Some(Url::parse("http://127.0.0.1:8081").unwrap()),
+            Network::Custom { faucet_url, .. } => faucet_url.clone(),
+        }
+    }
+
+    /// Public indexer GraphQL endpoint, for `IndexerClient`.
+    pub fn indexer_url(&self) -> Option<Url> {
+        match self {
+            Network::Mainnet => Some(Url::parse("https://indexer.mainnet.aptoslabs.com/v1/graphql").unwrap()),
+            Network::Testnet => Some(Url::parse("https://indexer-testnet.staging.gcp.aptosdev.com/v1/graphql").unwrap()),
+            Network::Devnet => Some(Url::parse("https://indexer-devnet.staging.gcp.aptosdev.com/v1/graphql").unwrap()),
+            Network::Local | Network::Custom { .. } => None,
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        self.chain_id() == Some(MAINNET_CHAIN_ID)
+    }
+
+    /// Networks without a known chain ID (devnet, custom networks without
+    /// one) still refuse a node reporting the mainnet or testnet chain ID,
+    /// so a misconfigured URL cannot send devnet writes to mainnet.
+    pub fn check_chain_id(&self, chain_id: u8) -> Result<()> {
+        match self.chain_id() {
+            Some(expected) if expected != chain_id => bail!(
+                "Chain ID mismatch: {} expects chain ID {}, but the node at {} reports {}",
+                self,
+                expected,
+                self.node_url(),
+                chain_id
+            ),
+            None if chain_id == MAINNET_CHAIN_ID || chain_id == TESTNET_CHAIN_ID => bail!(
+                "Chain ID mismatch: the node at {} reports chain ID {} ({}), but {} was selected; \
+                 use a network with that chain ID instead",
+                self.node_url(),
+                chain_id,
+                if chain_id == MAINNET_CHAIN_ID { "mainnet" } else { "testnet" },
+                self
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+            Network::Devnet => write!(f, "devnet"),
+            Network::Local => write!(f, "local"),
+            Network::Custom { node_url, .. } => write!(f, "custom ({})", node_url),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Only the names are case-insensitive: URL paths and API keys are not.
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            "local" => Ok(Network::Local),
+            _ => Ok(Network::Custom {
+                node_url: Url::parse(s)
+                    .map_err(|_| anyhow::anyhow!("Unknown network `{}`", s))?,
+                faucet_url: None,
+                chain_id: None,
+            }),
+        }
+    }
+}
+
+/// Called with the entry function name before a write method signs a
+/// transaction on mainnet. Returning an error aborts the write.
+#[derive(Clone)]
+pub struct MainnetConfirmation(Arc<dyn Fn(&str) -> Result<()> + Send + Sync>);
+
+impl MainnetConfirmation {
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&str) -> Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub fn confirm(&self, function_name: &str) -> Result<()> {
+        (self.0)(function_name)
+    }
+}
+
+impl fmt::Debug for MainnetConfirmation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MainnetConfirmation")
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use aptos_sdk::types::account_address::AccountAddress;
+
+use crate::{
+    events::get_all_events,
+    types::{
+        PendingOffer, Token, TokenCancelOfferEvent, TokenClaimEvent, TokenId, TokenOfferEvent,
+        TokenOfferId,
+    },
+    TokenClient,
+};
+
+/// How an offer left the pending claims.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OfferClosing {
+    Claimed,
+    Cancelled,
+}
+
+impl<'a> TokenClient<'a> {
+    /// Claims and cancellations of `sender`'s offers by offer id, each with
+    /// its version, in version order.
+    pub(crate) async fn list_closed_offers(
+        &self,
+        sender: AccountAddress,
+    ) -> Result<HashMap<TokenOfferId, Vec<(u64, OfferClosing)>>> {
+        let resources = match self.try_get_pending_claims_resources_for_account(sender).await? {
+            Some(resources) => resources,
+            None => return Ok(HashMap::new()),
+        };
+
+        let claims = get_all_events::<TokenClaimEvent>(
+            self.api_client,
+            sender,
+            "0x3::token_transfers::PendingClaims",
+            "claim_events",
+            resources.claim_events.counter.0,
+        ).await?;
+        let cancels = get_all_events::<TokenCancelOfferEvent>(
+            self.api_client,
+            sender,
+            "0x3::token_transfers::PendingClaims",
+            "cancel_offer_events",
+            resources.cancel_offer_events.counter.0,
+        ).await?;
+
+        let mut closed = HashMap::<TokenOfferId, Vec<(u64, OfferClosing)>>::new();
+        for (to_addr, token_id, version, closing) in claims
+            .into_iter()
+            .map(|e| (e.data.to_address, e.data.token_id, e.version, OfferClosing::Claimed))
+            .chain(cancels.into_iter().map(|e| (e.data.to_address, e.data.token_id, e.version, OfferClosing::Cancelled)))
+        {
+            closed.entry(TokenOfferId { to_addr, token_id }).or_default().push((version, closing));
+        }
+        for closings in closed.values_mut() {
+            closings.sort_by_key(|(version, _)| *version);
+        }
+        Ok(closed)
+    }
+
+    /// Offers `sender` has made that were neither claimed nor cancelled.
+    /// Candidates come from its offer, claim and cancel events; the amounts
+    /// come from the `pending_claims` table.
+    pub async fn list_outgoing_offers(&self, sender: AccountAddress) -> Result<Vec<PendingOffer>> {
+        let resources = match self.try_get_pending_claims_resources_for_account(sender).await? {
+            Some(resources) => resources,
+            // No `PendingClaims` resource: the account never offered anything.
+            None => return Ok(vec![]),
+        };
+
+        let offers = get_all_events::<TokenOfferEvent>(
+            self.api_client,
+            sender,
+            "0x3::token_transfers::PendingClaims",
+            "offer_events",
+            resources.offer_events.counter.0,
+        ).await?;
+        let claims = get_all_events::<TokenClaimEvent>(
+            self.api_client,
+            sender,
+            "0x3::token_transfers::PendingClaims",
+            "claim_events",
+            resources.claim_events.counter.0,
+        ).await?;
+        let cancels = get_all_events::<TokenCancelOfferEvent>(
+            self.api_client,
+            sender,
+            "0x3::token_transfers::PendingClaims",
+            "cancel_offer_events",
+            resources.cancel_offer_events.counter.0,
+        ).await?;
+
+        // Claims and cancels always take the whole offer, so only the last
+        // event per offer id matters.
+        let mut last_event = HashMap::<TokenOfferId, (u64, bool)>::new();
+        for event in offers {
+            let id = TokenOfferId { to_addr: event.data.to_address, token_id: event.data.token_id };
+            last_event.insert(id, (event.version, true));
+        }
+        for (to_addr, token_id, version) in claims
+            .into_iter()
+            .map(|e| (e.data.to_address, e.data.token_id, e.version))
+            .chain(cancels.into_iter().map(|e| (e.data.to_address, e.data.token_id, e.version)))
+        {
+            let entry = last_event.entry(TokenOfferId { to_addr, token_id }).or_insert((version, false));
+            if entry.0 <= version {
+                *entry = (version, false);
+            }
+        }
+
+        let mut pending = Vec::new();
+        for (offer_id, (_, open)) in last_event {
+            if !open {
+                continue;
+            }
+            if let Some(token) = self
+                .get_pending_offer_token(resources.pending_claims.handle, &offer_id)
+                .await?
+            {
+                pending.push(PendingOffer { sender, receiver: offer_id.to_addr, token });
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Offers made to `receiver` by any of `senders`. Offers are stored by
+    /// sender, so the receiver side can only be read for known senders.
+    pub async fn list_incoming_offers(
+        &self,
+        receiver: AccountAddress,
+        senders: &[AccountAddress],
+    ) -> Result<Vec<PendingOffer>> {
+        let mut incoming = Vec::new();
+        for sender in senders {
+            incoming.extend(
+                self.list_outgoing_offers(*sender)
+                    .await?
+                    .into_iter()
+                    .filter(|offer| offer.receiver == receiver),
+            );
+        }
+        Ok(incoming)
+    }
+
+    /// Amount of `token_id` that `sender` currently offers `receiver`, 0 if
+    /// there is no such offer.
+    pub async fn get_offer_amount(
+        &self,
+        sender: AccountAddress,
+        receiver: AccountAddress,
+        token_id: TokenId,
+    ) -> Result<u64> {
+        let resources = match self.try_get_pending_claims_resources_for_account(sender).await? {
+            Some(resources) => resources,
+            None => return Ok(0),
+        };
+
+        let offer_id = TokenOfferId { to_addr: receiver, token_id };
+        Ok(self
+            .get_pending_offer_token(resources.pending_claims.handle, &offer_id)
+            .await?
+            .map(|token| token.amount.0)
+            .unwrap_or(0))
+    }
+
+    async fn get_pending_offer_token(
+        &self,
+        pending_claims: AccountAddress,
+        offer_id: &TokenOfferId,
+    ) -> Result<Option<Token>> {
+        let item = self
+            .try_get_table_item(
+                pending_claims,
+                "0x3::token_transfers::TokenOfferId",
+                "0x3::token::Token",
+                offer_id,
+            )
+            .await
+            .context("Error on getting pending offer")?;
+
+        // Missing key: the offer was claimed or cancelled.
+        item.map(|item| serde_json::from_value::<Token>(item).context("Error on parsing pending offer"))
+            .transpose()
+    }
+}
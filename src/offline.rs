@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use aptos_sdk::{
+    bcs,
+    types::transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    explain::explain_raw_transaction,
+    signer::{sign_transaction, TransactionSigner},
+};
+
+/// `<address>::<module>::<function>` of an entry function payload, or the
+/// payload kind for scripts and module bundles.
+pub fn payload_function_name(payload: &TransactionPayload) -> String {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function) => format!(
+            "{}::{}::{}",
+            entry_function.module().address().to_hex_literal(),
+            entry_function.module().name(),
+            entry_function.function()
+        ),
+        TransactionPayload::Script(_) => "script".to_string(),
+        TransactionPayload::ModuleBundle(_) => "module bundle".to_string(),
+    }
+}
+
+/// A raw transaction built for signing on another machine. The sequence
+/// number and expiration are fixed when it is built, so it must be signed
+/// and submitted before it expires and before the sender sends anything
+/// else.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnsignedTransaction {
+    raw_txn: RawTransaction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedTransaction {
+    summary: String,
+    /// Plain-language description of the payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explanation: Option<String>,
+    bcs: String,
+}
+
+impl UnsignedTransaction {
+    pub fn new(raw_txn: RawTransaction) -> Self {
+        Self { raw_txn }
+    }
+
+    pub fn raw_transaction(&self) -> &RawTransaction {
+        &self.raw_txn
+    }
+
+    pub fn into_raw_transaction(self) -> RawTransaction {
+        self.raw_txn
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} from {} (sequence number {}), max gas {} at {} per unit, expires at {} (unix), chain ID {}",
+            payload_function_name(self.raw_txn.payload()),
+            self.raw_txn.sender().to_hex_literal(),
+            self.raw_txn.sequence_number(),
+            self.raw_txn.max_gas_amount(),
+            self.raw_txn.gas_unit_price(),
+            self.raw_txn.expiration_timestamp_secs(),
+            self.raw_txn.chain_id(),
+        )
+    }
+
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(&self.raw_txn).context("Failed to serialize raw transaction")
+    }
+
+    pub fn from_bcs(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::new(
+            bcs::from_bytes(bytes).context("Error on parsing raw transaction")?,
+        ))
+    }
+
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(format!("0x{}", hex::encode(self.to_bcs()?)))
+    }
+
+    pub fn from_hex(hex_str: &str) -> Result<Self> {
+        Self::from_bcs(&decode_hex(hex_str)?)
+    }
+
+    /// JSON carrying the summary alongside the BCS hex, so whoever signs
+    /// can see what they sign. Only the BCS part is read back.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&ExportedTransaction {
+            summary: self.summary(),
+            explanation: explain_raw_transaction(&self.raw_txn)
+                .ok()
+                .map(|explanation| explanation.summary),
+            bcs: self.to_hex()?,
+        })
+        .context("Failed to serialize raw transaction")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let exported: ExportedTransaction =
+            serde_json::from_str(json).context("Error on parsing exported transaction")?;
+        Self::from_hex(&exported.bcs)
+    }
+
+    pub async fn sign(self, signer: &dyn TransactionSigner) -> Result<SignedTransaction> {
+        sign_transaction(signer, self.raw_txn).await
+    }
+}
+
+pub fn signed_transaction_to_hex(signed_txn: &SignedTransaction) -> Result<String> {
+    Ok(format!(
+        "0x{}",
+        hex::encode(bcs::to_bytes(signed_txn).context("Failed to serialize signed transaction")?)
+    ))
+}
+
+pub fn signed_transaction_from_hex(hex_str: &str) -> Result<SignedTransaction> {
+    bcs::from_bytes(&decode_hex(hex_str)?).context("Error on parsing signed transaction")
+}
+
+pub(crate) fn decode_hex(hex_str: &str) -> Result<Vec<u8>> {
+    hex::decode(hex_str.trim().trim_start_matches("0x")).context("Invalid hex string")
+}
@@ -0,0 +1,619 @@
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{
+    bcs,
+    rest_client::aptos_api_types::U64,
+    types::{account_address::AccountAddress, transaction::TransactionPayload},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    multisig::decode_multisig_payload,
+    offline::{decode_hex, payload_function_name},
+    types::{RoyaltyPoints, TokenDataId, TokenId, TokenProperty},
+};
+
+/// A token entry function call with its arguments decoded.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum TokenOperation {
+    CreateCollection {
+        name: String,
+        description: String,
+        uri: String,
+        maximum: u64,
+        mutability: Vec<bool>,
+    },
+    CreateToken {
+        collection: String,
+        name: String,
+        description: String,
+        balance: u64,
+        maximum: u64,
+        uri: String,
+        royalty_payee: AccountAddress,
+        royalty: RoyaltyPoints,
+        mutability: Vec<bool>,
+        properties: TokenProperty,
+    },
+    Mint {
+        creator: AccountAddress,
+        collection: String,
+        name: String,
+        amount: u64,
+    },
+    MutateProperties {
+        token_owner: AccountAddress,
+        token_id: TokenId,
+        amount: u64,
+        properties: TokenProperty,
+    },
+    Burn {
+        token_id: TokenId,
+        amount: u64,
+    },
+    /// `direct_transfer_script` names its receiver as secondary signer
+    /// only, so the receiver is unknown when decoding the payload alone.
+    Transfer {
+        receiver: Option<AccountAddress>,
+        token_id: TokenId,
+        amount: u64,
+    },
+    OptInDirectTransfer {
+        opt_in: bool,
+    },
+    Offer {
+        receiver: AccountAddress,
+        token_id: TokenId,
+        amount: u64,
+    },
+    Claim {
+        sender: AccountAddress,
+        token_id: TokenId,
+    },
+    CancelOffer {
+        receiver: AccountAddress,
+        token_id: TokenId,
+    },
+    List {
+        token_id: TokenId,
+        amount: u64,
+        min_coin_per_token: u64,
+        locked_until_secs: u64,
+        coin_type: String,
+    },
+    /// `0x1::multisig_account::create_transaction`, with the proposed
+    /// payload decoded.
+    MultisigProposal {
+        multisig_account: AccountAddress,
+        proposal: Box<TokenOperation>,
+    },
+    /// `0x1::multisig_account::approve_transaction` or `reject_transaction`.
+    /// The proposal voted on is stored on chain, so decoding leaves it
+    /// `None`; `TokenClient` reads it before signing.
+    MultisigVote {
+        multisig_account: AccountAddress,
+        sequence_number: u64,
+        approve: bool,
+        proposal: Option<Box<TokenOperation>>,
+    },
+    /// Any other payload, by function name.
+    Other {
+        function: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    CreateCollection,
+    CreateToken,
+    Mint,
+    MutateProperties,
+    Burn,
+    Transfer,
+    OptInDirectTransfer,
+    Offer,
+    Claim,
+    CancelOffer,
+    List,
+    MultisigProposal,
+    MultisigVote,
+    Other,
+}
+
+/// An entry function argument, read from BCS or from the JSON of a
+/// committed transaction.
+trait MoveArg: DeserializeOwned {
+    fn from_json(value: &Value) -> Option<Self>;
+}
+
+impl MoveArg for bool {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+// The REST API renders u64 as a string.
+impl MoveArg for u64 {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_str().and_then(|value| value.parse().ok()).or_else(|| value.as_u64())
+    }
+}
+
+impl MoveArg for String {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_str().map(|value| value.to_string())
+    }
+}
+
+impl MoveArg for AccountAddress {
+    fn from_json(value: &Value) -> Option<Self> {
+        AccountAddress::from_hex_literal(value.as_str()?).ok()
+    }
+}
+
+// The REST API renders vector<u8> as a hex string.
+impl MoveArg for Vec<u8> {
+    fn from_json(value: &Value) -> Option<Self> {
+        decode_hex(value.as_str()?).ok()
+    }
+}
+
+impl MoveArg for Vec<bool> {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(bool::from_json).collect()
+    }
+}
+
+impl MoveArg for Vec<String> {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(String::from_json).collect()
+    }
+}
+
+impl MoveArg for Vec<Vec<u8>> {
+    fn from_json(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(Vec::<u8>::from_json).collect()
+    }
+}
+
+enum ArgValues<'a> {
+    Bcs(&'a [Vec<u8>]),
+    Json(&'a [Value]),
+}
+
+struct EntryArgs<'a> {
+    function: String,
+    args: ArgValues<'a>,
+    next: usize,
+}
+
+impl<'a> EntryArgs<'a> {
+    fn next<T: MoveArg>(&mut self, field: &str) -> Result<T> {
+        let index = self.next;
+        self.next += 1;
+        let missing = || format!("`{}` has no {} argument", self.function, field);
+        let invalid = || format!("Error on decoding {} of `{}`", field, self.function);
+
+        match &self.args {
+            ArgValues::Bcs(args) => bcs::from_bytes(args.get(index).with_context(missing)?).with_context(invalid),
+            ArgValues::Json(args) => T::from_json(args.get(index).with_context(missing)?).with_context(invalid),
+        }
+    }
+
+    fn token_id(&mut self, creator: AccountAddress) -> Result<TokenId> {
+        let collection = self.next("collection")?;
+        let name = self.next("name")?;
+        let property_version: u64 = self.next("property_version")?;
+        Ok(TokenId {
+            token_data_id: TokenDataId { creator, collection, name },
+            property_version: U64(property_version),
+        })
+    }
+
+    /// Property values are raw bytes on chain; ours are strings.
+    fn properties(&mut self) -> Result<TokenProperty> {
+        let keys = self.next("property_keys")?;
+        let values: Vec<Vec<u8>> = self.next("property_values")?;
+        let types = self.next("property_types")?;
+        Ok(TokenProperty {
+            keys,
+            values: values
+                .iter()
+                .map(|value| String::from_utf8_lossy(value).into_owned())
+                .collect(),
+            types,
+        })
+    }
+}
+
+impl TokenOperation {
+    /// Decodes `payload` if it calls a `0x3::token`, `0x3::token_transfers`
+    /// or `0x3::token_coin_swap` entry function, proposes one to a
+    /// multisig account or votes on a proposal. `secondary_signers` gives
+    /// the receiver of a direct transfer.
+    pub fn from_payload(payload: &TransactionPayload, secondary_signers: &[AccountAddress]) -> Result<Self> {
+        let function = payload_function_name(payload);
+        let entry_function = match payload {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => return Ok(TokenOperation::Other { function }),
+        };
+        let module = entry_function.module();
+
+        Self::decode(
+            *module.address(),
+            module.name().as_str(),
+            entry_function.function().as_str(),
+            entry_function.ty_args().first().map(|coin_type| coin_type.to_string()),
+            EntryArgs {
+                function,
+                args: ArgValues::Bcs(entry_function.args()),
+                next: 0,
+            },
+            secondary_signers,
+        )
+    }
+
+    /// Same as `from_payload`, for the `payload` of a transaction as
+    /// returned by the REST API.
+    pub fn from_json_payload(payload: &Value, secondary_signers: &[AccountAddress]) -> Result<Self> {
+        if payload["type"] != "entry_function_payload" {
+            return Ok(TokenOperation::Other {
+                function: payload["type"].as_str().unwrap_or("unknown payload").to_string(),
+            });
+        }
+        let function = payload["function"]
+            .as_str()
+            .context("Entry function payload has no function")?
+            .to_string();
+        let parts: Vec<&str> = function.split("::").collect();
+        let (address, module_name, function_name) = match parts.as_slice() {
+            [address, module_name, function_name] => (*address, *module_name, *function_name),
+            _ => bail!("Invalid entry function `{}`", function),
+        };
+        let address = AccountAddress::from_hex_literal(address)
+            .with_context(|| format!("Invalid module address in `{}`", function))?;
+        let args = payload["arguments"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+        Self::decode(
+            address,
+            module_name,
+            function_name,
+            payload["type_arguments"][0].as_str().map(|coin_type| coin_type.to_string()),
+            EntryArgs {
+                function: function.clone(),
+                args: ArgValues::Json(args),
+                next: 0,
+            },
+            secondary_signers,
+        )
+    }
+
+    fn decode(
+        address: AccountAddress,
+        module_name: &str,
+        function_name: &str,
+        coin_type: Option<String>,
+        mut args: EntryArgs<'_>,
+        secondary_signers: &[AccountAddress],
+    ) -> Result<Self> {
+        if address == AccountAddress::ONE && module_name == "multisig_account" && function_name == "create_transaction" {
+            let multisig_account = args.next("multisig_account")?;
+            let payload: Vec<u8> = args.next("payload")?;
+            let entry_function = decode_multisig_payload(&payload)?;
+            return Ok(TokenOperation::MultisigProposal {
+                multisig_account,
+                proposal: Box::new(Self::from_payload(&TransactionPayload::EntryFunction(entry_function), &[])?),
+            });
+        }
+        if address == AccountAddress::ONE
+            && module_name == "multisig_account"
+            && (function_name == "approve_transaction" || function_name == "reject_transaction")
+        {
+            return Ok(TokenOperation::MultisigVote {
+                multisig_account: args.next("multisig_account")?,
+                sequence_number: args.next("sequence_number")?,
+                approve: function_name == "approve_transaction",
+                proposal: None,
+            });
+        }
+        if address != AccountAddress::from_hex_literal("0x3").unwrap() {
+            return Ok(TokenOperation::Other { function: args.function });
+        }
+
+        let operation = match (module_name, function_name) {
+            ("token", "create_collection_script") => TokenOperation::CreateCollection {
+                name: args.next("name")?,
+                description: args.next("description")?,
+                uri: args.next("uri")?,
+                maximum: args.next("maximum")?,
+                mutability: args.next("mutate_setting")?,
+            },
+            ("token", "create_token_script") => {
+                let collection = args.next("collection")?;
+                let name = args.next("name")?;
+                let description = args.next("description")?;
+                let balance = args.next("balance")?;
+                let maximum = args.next("maximum")?;
+                let uri = args.next("uri")?;
+                let royalty_payee = args.next("royalty_payee_address")?;
+                let denominator = args.next("royalty_points_denominator")?;
+                let numerator = args.next("royalty_points_numerator")?;
+                TokenOperation::CreateToken {
+                    collection,
+                    name,
+                    description,
+                    balance,
+                    maximum,
+                    uri,
+                    royalty_payee,
+                    royalty: RoyaltyPoints { denominator, numerator },
+                    mutability: args.next("mutate_setting")?,
+                    properties: args.properties()?,
+                }
+            }
+            ("token", "mint_script") => TokenOperation::Mint {
+                creator: args.next("creator")?,
+                collection: args.next("collection")?,
+                name: args.next("name")?,
+                amount: args.next("amount")?,
+            },
+            ("token", "mutate_token_properties") => {
+                let token_owner = args.next("token_owner")?;
+                let creator = args.next("creator")?;
+                TokenOperation::MutateProperties {
+                    token_owner,
+                    token_id: args.token_id(creator)?,
+                    amount: args.next("amount")?,
+                    properties: args.properties()?,
+                }
+            }
+            ("token", "burn") => {
+                let creator = args.next("creator")?;
+                TokenOperation::Burn {
+                    token_id: args.token_id(creator)?,
+                    amount: args.next("amount")?,
+                }
+            }
+            ("token", "direct_transfer_script") => {
+                let creator = args.next("creator")?;
+                TokenOperation::Transfer {
+                    receiver: secondary_signers.first().copied(),
+                    token_id: args.token_id(creator)?,
+                    amount: args.next("amount")?,
+                }
+            }
+            ("token", "transfer_with_opt_in") => {
+                let creator = args.next("creator")?;
+                let token_id = args.token_id(creator)?;
+                TokenOperation::Transfer {
+                    receiver: Some(args.next("receiver")?),
+                    token_id,
+                    amount: args.next("amount")?,
+                }
+            }
+            ("token", "opt_in_direct_transfer") => TokenOperation::OptInDirectTransfer {
+                opt_in: args.next("opt_in")?,
+            },
+            ("token_transfers", "offer_script") => {
+                let receiver = args.next("receiver")?;
+                let creator = args.next("creator")?;
+                TokenOperation::Offer {
+                    receiver,
+                    token_id: args.token_id(creator)?,
+                    amount: args.next("amount")?,
+                }
+            }
+            ("token_transfers", "claim_script") => {
+                let sender = args.next("sender")?;
+                let creator = args.next("creator")?;
+                TokenOperation::Claim {
+                    sender,
+                    token_id: args.token_id(creator)?,
+                }
+            }
+            ("token_transfers", "cancel_offer_script") => {
+                let receiver = args.next("receiver")?;
+                let creator = args.next("creator")?;
+                TokenOperation::CancelOffer {
+                    receiver,
+                    token_id: args.token_id(creator)?,
+                }
+            }
+            ("token_coin_swap", "list_token_for_swap") => {
+                let creator = args.next("creator")?;
+                TokenOperation::List {
+                    token_id: args.token_id(creator)?,
+                    amount: args.next("token_amount")?,
+                    min_coin_per_token: args.next("min_coin_per_token")?,
+                    locked_until_secs: args.next("locked_until_secs")?,
+                    coin_type: coin_type.unwrap_or_default(),
+                }
+            }
+            _ => TokenOperation::Other { function: args.function },
+        };
+        Ok(operation)
+    }
+
+    pub fn kind(&self) -> OperationKind {
+        match self {
+            TokenOperation::CreateCollection { .. } => OperationKind::CreateCollection,
+            TokenOperation::CreateToken { .. } => OperationKind::CreateToken,
+            TokenOperation::Mint { .. } => OperationKind::Mint,
+            TokenOperation::MutateProperties { .. } => OperationKind::MutateProperties,
+            TokenOperation::Burn { .. } => OperationKind::Burn,
+            TokenOperation::Transfer { .. } => OperationKind::Transfer,
+            TokenOperation::OptInDirectTransfer { .. } => OperationKind::OptInDirectTransfer,
+            TokenOperation::Offer { .. } => OperationKind::Offer,
+            TokenOperation::Claim { .. } => OperationKind::Claim,
+            TokenOperation::CancelOffer { .. } => OperationKind::CancelOffer,
+            TokenOperation::List { .. } => OperationKind::List,
+            TokenOperation::MultisigProposal { .. } => OperationKind::MultisigProposal,
+            TokenOperation::MultisigVote { .. } => OperationKind::MultisigVote,
+            TokenOperation::Other { .. } => OperationKind::Other,
+        }
+    }
+}
+
+fn describe_token(token_id: &TokenId) -> String {
+    format!(
+        "\"{}\" (property version {}) of collection \"{}\" by {}",
+        token_id.token_data_id.name,
+        token_id.property_version.0,
+        token_id.token_data_id.collection,
+        token_id.token_data_id.creator.to_hex_literal()
+    )
+}
+
+fn describe_maximum(maximum: u64) -> String {
+    if maximum == 0 {
+        "unlimited".to_string()
+    } else {
+        maximum.to_string()
+    }
+}
+
+fn describe_royalty(royalty: &RoyaltyPoints, payee: AccountAddress) -> String {
+    if royalty.denominator == 0 || royalty.numerator == 0 {
+        return "no royalty".to_string();
+    }
+    format!(
+        "royalty {}/{} ({:.2}%) to {}",
+        royalty.numerator,
+        royalty.denominator,
+        royalty.numerator as f64 * 100.0 / royalty.denominator as f64,
+        payee.to_hex_literal()
+    )
+}
+
+fn describe_properties(properties: &TokenProperty) -> String {
+    if properties.keys.is_empty() {
+        return "no properties".to_string();
+    }
+    let properties: Vec<String> = properties
+        .keys
+        .iter()
+        .zip(properties.values.iter())
+        .zip(properties.types.iter())
+        .map(|((key, value), type_name)| format!("{} = {} ({})", key, value, type_name))
+        .collect();
+    format!("properties {}", properties.join(", "))
+}
+
+/// A plain-language sentence, e.g. for signing prompts and audit logs.
+impl fmt::Display for TokenOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenOperation::CreateCollection { name, description, uri, maximum, .. } => write!(
+                f,
+                "Create collection \"{}\" (\"{}\", {}) with maximum supply {}",
+                name,
+                description,
+                uri,
+                describe_maximum(*maximum)
+            ),
+            TokenOperation::CreateToken {
+                collection,
+                name,
+                balance,
+                maximum,
+                uri,
+                royalty_payee,
+                royalty,
+                properties,
+                ..
+            } => write!(
+                f,
+                "Create token \"{}\" ({}) in collection \"{}\" with balance {} and maximum supply {}, {}, {}",
+                name,
+                uri,
+                collection,
+                balance,
+                describe_maximum(*maximum),
+                describe_royalty(royalty, *royalty_payee),
+                describe_properties(properties)
+            ),
+            TokenOperation::Mint { creator, collection, name, amount } => write!(
+                f,
+                "Mint {} of \"{}\" of collection \"{}\" by {}",
+                amount,
+                name,
+                collection,
+                creator.to_hex_literal()
+            ),
+            TokenOperation::MutateProperties { token_owner, token_id, amount, properties } => write!(
+                f,
+                "Set {} on {} of {} owned by {}",
+                describe_properties(properties),
+                amount,
+                describe_token(token_id),
+                token_owner.to_hex_literal()
+            ),
+            TokenOperation::Burn { token_id, amount } => {
+                write!(f, "Burn {} of {}", amount, describe_token(token_id))
+            }
+            TokenOperation::Transfer { receiver, token_id, amount } => write!(
+                f,
+                "Transfer {} of {} to {}",
+                amount,
+                describe_token(token_id),
+                receiver
+                    .map(|receiver| receiver.to_hex_literal())
+                    .unwrap_or_else(|| "the co-signing receiver".to_string())
+            ),
+            TokenOperation::OptInDirectTransfer { opt_in: true } => write!(f, "Opt in to direct transfers"),
+            TokenOperation::OptInDirectTransfer { opt_in: false } => write!(f, "Opt out of direct transfers"),
+            TokenOperation::Offer { receiver, token_id, amount } => write!(
+                f,
+                "Offer {} of {} to {}",
+                amount,
+                describe_token(token_id),
+                receiver.to_hex_literal()
+            ),
+            TokenOperation::Claim { sender, token_id } => write!(
+                f,
+                "Claim {} offered by {}",
+                describe_token(token_id),
+                sender.to_hex_literal()
+            ),
+            TokenOperation::CancelOffer { receiver, token_id } => write!(
+                f,
+                "Cancel the offer of {} to {}",
+                describe_token(token_id),
+                receiver.to_hex_literal()
+            ),
+            TokenOperation::List { token_id, amount, min_coin_per_token, locked_until_secs, coin_type } => write!(
+                f,
+                "List {} of {} for swap at {} {} per token at least, locked until {} (unix)",
+                amount,
+                describe_token(token_id),
+                min_coin_per_token,
+                coin_type,
+                locked_until_secs
+            ),
+            TokenOperation::MultisigProposal { multisig_account, proposal } => write!(
+                f,
+                "Propose to multisig account {}: {}",
+                multisig_account.to_hex_literal(),
+                proposal
+            ),
+            TokenOperation::MultisigVote { multisig_account, sequence_number, approve, proposal } => {
+                write!(
+                    f,
+                    "{} proposal {} of multisig account {}",
+                    if *approve { "Approve" } else { "Reject" },
+                    sequence_number,
+                    multisig_account.to_hex_literal()
+                )?;
+                match proposal {
+                    Some(proposal) => write!(f, ": {}", proposal),
+                    None => Ok(()),
+                }
+            }
+            TokenOperation::Other { function } => write!(f, "Call {}", function),
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use aptos_sdk::types::account_address::AccountAddress;
+
+use crate::{
+    network::MAINNET_CHAIN_ID,
+    operation::{OperationKind, TokenOperation},
+};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// What a rule knows about the transaction besides its operation.
+#[derive(Clone, Debug)]
+pub struct PolicyContext {
+    pub sender: AccountAddress,
+    pub chain_id: u8,
+}
+
+impl PolicyContext {
+    pub fn is_mainnet(&self) -> bool {
+        self.chain_id == MAINNET_CHAIN_ID
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyError {
+    ReceiverNotAllowed { receiver: AccountAddress },
+    UnknownReceiver,
+    AmountExceedsLimit { amount: u64, max: u64 },
+    BurnForbidden,
+    RoyaltyExceedsCap { numerator: u64, denominator: u64, max_basis_points: u64 },
+    ApprovalDenied { kind: OperationKind },
+    RateLimited { kind: OperationKind, max_per_hour: usize },
+    UnknownOperation { function: String },
+    UnknownProposal { multisig_account: AccountAddress, sequence_number: u64 },
+    /// Raised by rules outside this module.
+    Rejected { rule: String, reason: String },
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::ReceiverNotAllowed { receiver } => {
+                write!(f, "Receiver {} is not allowed", receiver.to_hex_literal())
+            }
+            PolicyError::UnknownReceiver => write!(f, "Transfer receiver is unknown"),
+            PolicyError::AmountExceedsLimit { amount, max } => {
+                write!(f, "Amount {} exceeds the limit of {}", amount, max)
+            }
+            PolicyError::BurnForbidden => write!(f, "Burning tokens is forbidden"),
+            PolicyError::RoyaltyExceedsCap { numerator, denominator, max_basis_points } => write!(
+                f,
+                "Royalty {}/{} exceeds the cap of {} basis points",
+                numerator, denominator, max_basis_points
+            ),
+            PolicyError::ApprovalDenied { kind } => write!(f, "Mainnet approval denied for {:?}", kind),
+            PolicyError::RateLimited { kind, max_per_hour } => {
+                write!(f, "Limit of {} {:?} operations per hour reached", max_per_hour, kind)
+            }
+            PolicyError::UnknownOperation { function } => write!(f, "Unknown operation {} is not allowed", function),
+            PolicyError::UnknownProposal { multisig_account, sequence_number } => write!(
+                f,
+                "Approving proposal {} of multisig account {} is not allowed, its payload is unknown",
+                sequence_number,
+                multisig_account.to_hex_literal()
+            ),
+            PolicyError::Rejected { rule, reason } => write!(f, "Rejected by {}: {}", rule, reason),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+/// A check run on every operation before it is signed.
+pub trait PolicyRule: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Rules that count operations reserve their slot here, while they
+    /// still hold their lock.
+    fn check(&self, context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError>;
+
+    /// Gives back what `check` reserved, when a later rule rejected the
+    /// operation or it was not signed after all.
+    fn release(&self, _context: &PolicyContext, _operation: &TokenOperation) {}
+}
+
+/// Only transfers and offers to these receivers are signed.
+pub struct AllowedReceivers(pub HashSet<AccountAddress>);
+
+impl PolicyRule for AllowedReceivers {
+    fn name(&self) -> &str {
+        "allowed receivers"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        let receiver = match operation {
+            TokenOperation::Transfer { receiver, .. } => receiver.ok_or(PolicyError::UnknownReceiver)?,
+            TokenOperation::Offer { receiver, .. } => *receiver,
+            _ => return Ok(()),
+        };
+        if self.0.contains(&receiver) {
+            Ok(())
+        } else {
+            Err(PolicyError::ReceiverNotAllowed { receiver })
+        }
+    }
+}
+
+/// Caps the amount of a single transfer or offer.
+pub struct MaxTransferAmount(pub u64);
+
+impl PolicyRule for MaxTransferAmount {
+    fn name(&self) -> &str {
+        "max transfer amount"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        match operation {
+            TokenOperation::Transfer { amount, .. } | TokenOperation::Offer { amount, .. } if *amount > self.0 => {
+                Err(PolicyError::AmountExceedsLimit { amount: *amount, max: self.0 })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct ForbidBurns;
+
+impl PolicyRule for ForbidBurns {
+    fn name(&self) -> &str {
+        "forbid burns"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        match operation {
+            TokenOperation::Burn { .. } => Err(PolicyError::BurnForbidden),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Caps the royalty of created tokens, in basis points (1/10000).
+pub struct RoyaltyCap(pub u64);
+
+impl PolicyRule for RoyaltyCap {
+    fn name(&self) -> &str {
+        "royalty cap"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        let royalty = match operation {
+            TokenOperation::CreateToken { royalty, .. } => royalty,
+            _ => return Ok(()),
+        };
+        // A zero denominator means no royalty.
+        if royalty.denominator != 0
+            && royalty.numerator as u128 * 10_000 > self.0 as u128 * royalty.denominator as u128
+        {
+            return Err(PolicyError::RoyaltyExceedsCap {
+                numerator: royalty.numerator,
+                denominator: royalty.denominator,
+                max_basis_points: self.0,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Rejects payloads that do not decode to a token operation, and
+/// approvals of multisig proposals whose payload could not be read, since
+/// no other rule can tell what they do.
+pub struct DenyUnknown;
+
+impl PolicyRule for DenyUnknown {
+    fn name(&self) -> &str {
+        "deny unknown"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        match operation {
+            TokenOperation::Other { function } => Err(PolicyError::UnknownOperation { function: function.clone() }),
+            TokenOperation::MultisigVote { multisig_account, sequence_number, approve: true, proposal: None } => {
+                Err(PolicyError::UnknownProposal {
+                    multisig_account: *multisig_account,
+                    sequence_number: *sequence_number,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// On mainnet, operations of the given kinds are signed only once
+/// `approve` returns true.
+pub struct MainnetApproval {
+    kinds: HashSet<OperationKind>,
+    approve: Arc<dyn Fn(&PolicyContext, &TokenOperation) -> bool + Send + Sync>,
+}
+
+impl MainnetApproval {
+    pub fn new<F>(kinds: impl IntoIterator<Item = OperationKind>, approve: F) -> Self
+    where
+        F: Fn(&PolicyContext, &TokenOperation) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            kinds: kinds.into_iter().collect(),
+            approve: Arc::new(approve),
+        }
+    }
+}
+
+impl PolicyRule for MainnetApproval {
+    fn name(&self) -> &str {
+        "mainnet approval"
+    }
+
+    fn check(&self, context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        let kind = operation.kind();
+        if !context.is_mainnet() || !self.kinds.contains(&kind) || (self.approve)(context, operation) {
+            Ok(())
+        } else {
+            Err(PolicyError::ApprovalDenied { kind })
+        }
+    }
+}
+
+/// At most `max_per_hour` operations of a kind in any sliding hour,
+/// across all senders. Only operations every rule accepted are counted.
+/// A slot is taken as soon as the operation passes this rule, so
+/// concurrent checks cannot overshoot the limit.
+pub struct RateLimit {
+    kind: OperationKind,
+    max_per_hour: usize,
+    window: Duration,
+    signed_at: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimit {
+    pub fn new(kind: OperationKind, max_per_hour: usize) -> Self {
+        Self {
+            kind,
+            max_per_hour,
+            window: RATE_LIMIT_WINDOW,
+            signed_at: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Slides over `window` instead of an hour.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+impl PolicyRule for RateLimit {
+    fn name(&self) -> &str {
+        "rate limit"
+    }
+
+    fn check(&self, _context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        if operation.kind() != self.kind {
+            return Ok(());
+        }
+        let mut signed_at = self.signed_at.lock().unwrap();
+        while signed_at.front().map_or(false, |at| at.elapsed() >= self.window) {
+            signed_at.pop_front();
+        }
+        if signed_at.len() >= self.max_per_hour {
+            return Err(PolicyError::RateLimited { kind: self.kind, max_per_hour: self.max_per_hour });
+        }
+        signed_at.push_back(Instant::now());
+        Ok(())
+    }
+
+    fn release(&self, _context: &PolicyContext, operation: &TokenOperation) {
+        if operation.kind() == self.kind {
+            self.signed_at.lock().unwrap().pop_back();
+        }
+    }
+}
+
+/// `operation` and, for multisig proposals and approvals, the proposed
+/// operation as the multisig account will run it. Rejections run nothing.
+fn checked_operations<'o>(
+    context: &PolicyContext,
+    operation: &'o TokenOperation,
+) -> Vec<(PolicyContext, &'o TokenOperation)> {
+    let mut operations = vec![(context.clone(), operation)];
+    let proposed = match operation {
+        TokenOperation::MultisigProposal { multisig_account, proposal } => Some((multisig_account, proposal)),
+        TokenOperation::MultisigVote { multisig_account, approve: true, proposal: Some(proposal), .. } => {
+            Some((multisig_account, proposal))
+        }
+        _ => None,
+    };
+    if let Some((multisig_account, proposal)) = proposed {
+        let context = PolicyContext {
+            sender: *multisig_account,
+            chain_id: context.chain_id,
+        };
+        operations.extend(checked_operations(&context, proposal));
+    }
+    operations
+}
+
+/// Rules checked, in order, before `TokenClient` signs anything. Clones
+/// share the rules and their rate limit counters.
+#[derive(Clone, Default)]
+pub struct SigningPolicy {
+    rules: Vec<Arc<dyn PolicyRule>>,
+}
+
+impl SigningPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(mut self, rule: impl PolicyRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    pub fn allowed_receivers(self, receivers: impl IntoIterator<Item = AccountAddress>) -> Self {
+        self.with_rule(AllowedReceivers(receivers.into_iter().collect()))
+    }
+
+    pub fn max_transfer_amount(self, max: u64) -> Self {
+        self.with_rule(MaxTransferAmount(max))
+    }
+
+    pub fn forbid_burns(self) -> Self {
+        self.with_rule(ForbidBurns)
+    }
+
+    pub fn max_royalty_basis_points(self, max: u64) -> Self {
+        self.with_rule(RoyaltyCap(max))
+    }
+
+    pub fn mainnet_approval<F>(self, kinds: impl IntoIterator<Item = OperationKind>, approve: F) -> Self
+    where
+        F: Fn(&PolicyContext, &TokenOperation) -> bool + Send + Sync + 'static,
+    {
+        self.with_rule(MainnetApproval::new(kinds, approve))
+    }
+
+    pub fn rate_limit(self, kind: OperationKind, max_per_hour: usize) -> Self {
+        self.with_rule(RateLimit::new(kind, max_per_hour))
+    }
+
+    pub fn deny_unknown(self) -> Self {
+        self.with_rule(DenyUnknown)
+    }
+
+    /// Fails with the first rule that rejects `operation`, or the operation
+    /// a multisig proposal or approval runs, releasing what earlier rules
+    /// reserved.
+    pub fn check(&self, context: &PolicyContext, operation: &TokenOperation) -> Result<(), PolicyError> {
+        let operations = checked_operations(context, operation);
+        let mut passed = Vec::new();
+        for (context, operation) in &operations {
+            for rule in &self.rules {
+                if let Err(e) = rule.check(context, operation) {
+                    for (rule, context, operation) in passed.into_iter().rev() {
+                        rule.release(context, operation);
+                    }
+                    return Err(e);
+                }
+                passed.push((rule.as_ref(), context, *operation));
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes an accepted `check` for an operation that was not signed
+    /// after all.
+    pub fn release(&self, context: &PolicyContext, operation: &TokenOperation) {
+        for (context, operation) in checked_operations(context, operation).iter().rev() {
+            for rule in self.rules.iter().rev() {
+                rule.release(context, operation);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for SigningPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rules.iter().map(|rule| rule.name())).finish()
+    }
+}
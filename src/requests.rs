@@ -0,0 +1,176 @@
+use anyhow::Result;
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{CollectionOptions, RoyaltyPoints, TokenMutability, TokenProperty},
+    validation::{validate_collection_request, validate_token_request},
+};
+
+/// Arguments of `0x3::token::create_collection_script`.
+///
+/// A `maximum` of 0 means the collection has no supply limit.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateCollectionRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub maximum: u64,
+    #[serde(default)]
+    pub mutability: CollectionOptions,
+}
+
+impl CreateCollectionRequest {
+    pub fn builder(name: impl Into<String>) -> CreateCollectionRequestBuilder {
+        CreateCollectionRequestBuilder {
+            request: CreateCollectionRequest {
+                name: name.into(),
+                description: String::new(),
+                uri: String::new(),
+                maximum: 0,
+                mutability: CollectionOptions::default(),
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        Ok(validate_collection_request(self)?)
+    }
+}
+
+pub struct CreateCollectionRequestBuilder {
+    request: CreateCollectionRequest,
+}
+
+impl CreateCollectionRequestBuilder {
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.request.description = description.into();
+        self
+    }
+
+    pub fn uri(mut self, uri: impl Into<String>) -> Self {
+        self.request.uri = uri.into();
+        self
+    }
+
+    pub fn maximum(mut self, maximum: u64) -> Self {
+        self.request.maximum = maximum;
+        self
+    }
+
+    pub fn mutability(mut self, mutability: CollectionOptions) -> Self {
+        self.request.mutability = mutability;
+        self
+    }
+
+    pub fn build(self) -> Result<CreateCollectionRequest> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
+}
+
+/// Arguments of `0x3::token::create_token_script`.
+///
+/// Defaults describe a 1-of-1 token without royalty: `supply` (the amount
+/// minted to the creator) and `maximum` are both 1. A `royalty_payee` of
+/// `None` pays royalties to the creator.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CreateTokenRequest {
+    pub collection: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default = "default_token_amount")]
+    pub supply: u64,
+    #[serde(default = "default_token_amount")]
+    pub maximum: u64,
+    #[serde(default)]
+    pub royalty_payee: Option<AccountAddress>,
+    #[serde(default)]
+    pub royalty: RoyaltyPoints,
+    #[serde(default)]
+    pub mutability: TokenMutability,
+    #[serde(default)]
+    pub properties: TokenProperty,
+}
+
+fn default_token_amount() -> u64 {
+    1
+}
+
+impl CreateTokenRequest {
+    pub fn builder(collection: impl Into<String>, name: impl Into<String>) -> CreateTokenRequestBuilder {
+        CreateTokenRequestBuilder {
+            request: CreateTokenRequest {
+                collection: collection.into(),
+                name: name.into(),
+                description: String::new(),
+                uri: String::new(),
+                supply: default_token_amount(),
+                maximum: default_token_amount(),
+                royalty_payee: None,
+                royalty: RoyaltyPoints::default(),
+                mutability: TokenMutability::default(),
+                properties: TokenProperty::default(),
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        Ok(validate_token_request(self)?)
+    }
+}
+
+pub struct CreateTokenRequestBuilder {
+    request: CreateTokenRequest,
+}
+
+impl CreateTokenRequestBuilder {
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.request.description = description.into();
+        self
+    }
+
+    pub fn uri(mut self, uri: impl Into<String>) -> Self {
+        self.request.uri = uri.into();
+        self
+    }
+
+    pub fn supply(mut self, supply: u64) -> Self {
+        self.request.supply = supply;
+        self
+    }
+
+    pub fn maximum(mut self, maximum: u64) -> Self {
+        self.request.maximum = maximum;
+        self
+    }
+
+    pub fn royalty(mut self, payee: AccountAddress, royalty: RoyaltyPoints) -> Self {
+        self.request.royalty_payee = Some(payee);
+        self.request.royalty = royalty;
+        self
+    }
+
+    pub fn mutability(mut self, mutability: TokenMutability) -> Self {
+        self.request.mutability = mutability;
+        self
+    }
+
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<String>, type_: impl Into<String>) -> Self {
+        self.request.properties.keys.push(key.into());
+        self.request.properties.values.push(value.into());
+        self.request.properties.types.push(type_.into());
+        self
+    }
+
+    pub fn build(self) -> Result<CreateTokenRequest> {
+        self.request.validate()?;
+        Ok(self.request)
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::{bail, Context, Result};
+use aptos_sdk::{
+    bcs,
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        hash::{CryptoHash, CryptoHasher},
+        Signature, SigningKey,
+    },
+    rest_client::Client as ApiClient,
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+            SignedTransaction,
+        },
+        LocalAccount,
+    },
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Anything that can authorize token transactions for one account: an
+/// in-process `LocalAccount`, a KMS/HSM key or a remote signing service.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    fn address(&self) -> AccountAddress;
+
+    fn public_key(&self) -> Ed25519PublicKey;
+
+    /// Returns the sequence number to use for the next transaction and
+    /// advances the signer's counter.
+    async fn next_sequence_number(&mut self) -> Result<u64>;
+
+    /// Resets the counter, e.g. to the on-chain sequence number. Does
+    /// nothing by default, which suits signers whose
+    /// `next_sequence_number` reads the chain; signers keeping a local
+    /// counter must override it so journal runs can recover from expired
+    /// transactions.
+    fn set_sequence_number(&mut self, _sequence_number: u64) {}
+
+    async fn sign_raw_transaction(&self, raw_txn: &RawTransaction) -> Result<Ed25519Signature>;
+
+    async fn sign_multi_agent_transaction(
+        &self,
+        message: &RawTransactionWithData,
+    ) -> Result<Ed25519Signature>;
+}
+
+#[async_trait]
+impl TransactionSigner for LocalAccount {
+    fn address(&self) -> AccountAddress {
+        LocalAccount::address(self)
+    }
+
+    fn public_key(&self) -> Ed25519PublicKey {
+        LocalAccount::public_key(self).clone()
+    }
+
+    async fn next_sequence_number(&mut self) -> Result<u64> {
+        Ok(self.increment_sequence_number())
+    }
+
+    fn set_sequence_number(&mut self, sequence_number: u64) {
+        *self.sequence_number_mut() = sequence_number;
+    }
+
+    async fn sign_raw_transaction(&self, raw_txn: &RawTransaction) -> Result<Ed25519Signature> {
+        Ok(self.private_key().sign(raw_txn))
+    }
+
+    async fn sign_multi_agent_transaction(
+        &self,
+        message: &RawTransactionWithData,
+    ) -> Result<Ed25519Signature> {
+        Ok(self.private_key().sign(message))
+    }
+}
+
+pub async fn sign_transaction(
+    signer: &dyn TransactionSigner,
+    raw_txn: RawTransaction,
+) -> Result<SignedTransaction> {
+    let signature = signer.sign_raw_transaction(&raw_txn).await?;
+    Ok(SignedTransaction::new(raw_txn, signer.public_key(), signature))
+}
+
+pub async fn sign_multi_agent_transaction(
+    sender: &dyn TransactionSigner,
+    secondary_signers: &[&dyn TransactionSigner],
+    raw_txn: RawTransaction,
+) -> Result<SignedTransaction> {
+    let secondary_signer_addresses: Vec<AccountAddress> =
+        secondary_signers.iter().map(|signer| signer.address()).collect();
+    let message = RawTransactionWithData::new_multi_agent(
+        raw_txn.clone(),
+        secondary_signer_addresses.clone(),
+    );
+
+    let sender_authenticator = AccountAuthenticator::ed25519(
+        sender.public_key(),
+        sender.sign_multi_agent_transaction(&message).await?,
+    );
+    let mut secondary_authenticators = Vec::with_capacity(secondary_signers.len());
+    for signer in secondary_signers {
+        secondary_authenticators.push(AccountAuthenticator::ed25519(
+            signer.public_key(),
+            signer.sign_multi_agent_transaction(&message).await?,
+        ));
+    }
+
+    Ok(SignedTransaction::new_multi_agent(
+        raw_txn,
+        sender_authenticator,
+        secondary_signer_addresses,
+        secondary_authenticators,
+    ))
+}
+
+/// The bytes an Ed25519 key signs for `message`: the hasher's domain
+/// separator followed by the BCS encoding.
+pub fn signing_message<T: CryptoHash + Serialize>(message: &T) -> Result<Vec<u8>> {
+    let mut bytes = <T::Hasher as CryptoHasher>::seed().to_vec();
+    bcs::serialize_into(&mut bytes, message).context("Failed to serialize signing message")?;
+    Ok(bytes)
+}
+
+#[derive(Serialize)]
+struct RemoteSignRequest {
+    address: String,
+    public_key: String,
+    signing_message: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteSignResponse {
+    signature: String,
+}
+
+/// Signs through an HTTP service holding the key. The service receives
+/// `{"address", "public_key", "signing_message"}` (hex strings) as a JSON
+/// POST and answers `{"signature"}`. Signatures are verified against the
+/// configured public key before use.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: Url,
+    auth_token: Option<String>,
+    address: AccountAddress,
+    public_key: Ed25519PublicKey,
+    sequence_number: u64,
+}
+
+impl RemoteSigner {
+    pub fn new(url: Url, address: AccountAddress, public_key: Ed25519PublicKey, sequence_number: u64) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            auth_token: None,
+            address,
+            public_key,
+            sequence_number,
+        }
+    }
+
+    /// Sent as a bearer token with every signing request.
+    pub fn with_auth_token(mut self, auth_token: impl Into<String>) -> Self {
+        self.auth_token = Some(auth_token.into());
+        self
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn set_sequence_number(&mut self, sequence_number: u64) {
+        self.sequence_number = sequence_number;
+    }
+
+    /// Resets the local counter to the on-chain sequence number.
+    pub async fn sync_sequence_number(&mut self, api_client: &ApiClient) -> Result<()> {
+        self.sequence_number = api_client
+            .get_account(self.address)
+            .await
+            .context("Failed to get account sequence number")?
+            .into_inner()
+            .sequence_number;
+        Ok(())
+    }
+
+    async fn request_signature<T: CryptoHash + Serialize + Sync>(&self, message: &T) -> Result<Ed25519Signature> {
+        let body = RemoteSignRequest {
+            address: self.address.to_hex_literal(),
+            public_key: format!("0x{}", hex::encode(self.public_key.to_bytes())),
+            signing_message: format!("0x{}", hex::encode(signing_message(message)?)),
+        };
+
+        let mut request = self.client.post(self.url.clone()).json(&body);
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to reach remote signer")?;
+        if !response.status().is_success() {
+            bail!("Remote signer refused to sign: HTTP {}", response.status());
+        }
+        let response: RemoteSignResponse = response
+            .json()
+            .await
+            .context("Error on parsing remote signer response")?;
+
+        let bytes = hex::decode(response.signature.trim_start_matches("0x"))
+            .context("Remote signer returned a non-hex signature")?;
+        let signature = Ed25519Signature::try_from(bytes.as_slice())
+            .context("Remote signer returned an invalid signature")?;
+        signature
+            .verify(message, &self.public_key)
+            .context("Remote signer returned a signature that does not match its public key")?;
+        Ok(signature)
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn address(&self) -> AccountAddress {
+        self.address
+    }
+
+    fn public_key(&self) -> Ed25519PublicKey {
+        self.public_key.clone()
+    }
+
+    async fn next_sequence_number(&mut self) -> Result<u64> {
+        let sequence_number = self.sequence_number;
+        self.sequence_number += 1;
+        Ok(sequence_number)
+    }
+
+    fn set_sequence_number(&mut self, sequence_number: u64) {
+        RemoteSigner::set_sequence_number(self, sequence_number)
+    }
+
+    async fn sign_raw_transaction(&self, raw_txn: &RawTransaction) -> Result<Ed25519Signature> {
+        self.request_signature(raw_txn).await
+    }
+
+    async fn sign_multi_agent_transaction(
+        &self,
+        message: &RawTransactionWithData,
+    ) -> Result<Ed25519Signature> {
+        self.request_signature(message).await
+    }
+}
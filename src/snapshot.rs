@@ -0,0 +1,276 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use aptos_sdk::{bcs, types::account_address::AccountAddress};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::{
+    events::get_all_events,
+    types::{DepositEvent, TokenDataId, TokenId, TokenStoreResources, WithdrawEvent},
+    TokenClient,
+};
+
+/// What a snapshot counts holdings of.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotTarget {
+    Collection { creator: AccountAddress, collection: String },
+    TokenData(TokenDataId),
+}
+
+impl SnapshotTarget {
+    pub fn creator(&self) -> AccountAddress {
+        match self {
+            SnapshotTarget::Collection { creator, .. } => *creator,
+            SnapshotTarget::TokenData(token_data_id) => token_data_id.creator,
+        }
+    }
+
+    pub fn matches(&self, token_id: &TokenId) -> bool {
+        match self {
+            SnapshotTarget::Collection { creator, collection } => {
+                token_id.token_data_id.creator == *creator && token_id.token_data_id.collection == *collection
+            }
+            SnapshotTarget::TokenData(token_data_id) => token_id.token_data_id == *token_data_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HolderBalance {
+    pub owner: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: u64,
+}
+
+impl HolderBalance {
+    /// SHA3-256 of the BCS encoding of `(owner, creator, collection, name,
+    /// property_version, amount)`, so the leaf can be rebuilt in Move.
+    pub fn leaf_hash(&self) -> Result<[u8; 32]> {
+        let token_data_id = &self.token_id.token_data_id;
+        let bytes = bcs::to_bytes(&(
+            self.owner,
+            token_data_id.creator,
+            &token_data_id.collection,
+            &token_data_id.name,
+            self.token_id.property_version.0,
+            self.amount,
+        ))
+        .context("Failed to serialize snapshot leaf")?;
+        Ok(Sha3_256::digest(&bytes).into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotEntry {
+    #[serde(flatten)]
+    pub holder: HolderBalance,
+    /// Hex encoded sibling hashes from the leaf up to the root.
+    pub proof: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Snapshot {
+    pub target: SnapshotTarget,
+    pub ledger_version: u64,
+    pub merkle_root: String,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha3_256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// Pairs are hashed in sorted order, so proofs need no left/right flags.
+/// An odd node at the end of a level is carried up unchanged.
+fn merkle_tree(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn merkle_proof(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
+
+pub fn verify_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling)) == root
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl Snapshot {
+    /// Sorts holders and computes the Merkle root and proofs. An empty
+    /// snapshot has an all-zero root.
+    pub fn new(target: SnapshotTarget, ledger_version: u64, mut holders: Vec<HolderBalance>) -> Result<Self> {
+        holders.sort_by(|a, b| {
+            (a.owner, &a.token_id.token_data_id.name, a.token_id.property_version.0).cmp(&(
+                b.owner,
+                &b.token_id.token_data_id.name,
+                b.token_id.property_version.0,
+            ))
+        });
+
+        let levels = merkle_tree(holders.iter().map(HolderBalance::leaf_hash).collect::<Result<_>>()?);
+        let merkle_root = levels.last().unwrap().first().copied().unwrap_or([0u8; 32]);
+        let entries = holders
+            .into_iter()
+            .enumerate()
+            .map(|(index, holder)| SnapshotEntry {
+                holder,
+                proof: merkle_proof(&levels, index).iter().map(hex::encode).collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            target,
+            ledger_version,
+            merkle_root: hex::encode(merkle_root),
+            entries,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize snapshot")
+    }
+
+    /// One row per holder; the proof column is `;`-separated. The Merkle
+    /// root and ledger version come first as `#` comment lines.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!(
+            "# merkle_root: {}\n# ledger_version: {}\nowner,creator,collection,name,property_version,amount,proof\n",
+            self.merkle_root, self.ledger_version
+        );
+        for entry in &self.entries {
+            let token_data_id = &entry.holder.token_id.token_data_id;
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                entry.holder.owner.to_hex_literal(),
+                token_data_id.creator.to_hex_literal(),
+                csv_field(&token_data_id.collection),
+                csv_field(&token_data_id.name),
+                entry.holder.token_id.property_version.0,
+                entry.holder.amount,
+                entry.proof.join(";"),
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_csv()).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+impl<'a> TokenClient<'a> {
+    /// Balances of `target` held by each of `candidates` at
+    /// `ledger_version`, from replaying their deposit and withdraw events.
+    ///
+    /// The chain has no reverse index from a token to its holders, so
+    /// candidates must come from elsewhere, e.g. `IndexerClient` or the
+    /// receivers of past airdrops. The creator is always included.
+    pub async fn snapshot_holders(
+        &self,
+        target: SnapshotTarget,
+        candidates: &[AccountAddress],
+        ledger_version: u64,
+    ) -> Result<Snapshot> {
+        let mut accounts = BTreeSet::from([target.creator()]);
+        accounts.extend(candidates.iter().copied());
+
+        let mut holders = Vec::new();
+        for account in accounts {
+            let resource = match self
+                .get_account_resource_at_version(account, "0x3::token::TokenStore", ledger_version)
+                .await?
+            {
+                Some(resource) => resource,
+                None => continue,
+            };
+            let data: TokenStoreResources = serde_json::from_value(resource)
+                .context("Error on parsing token store resources")?;
+
+            // The counters are read at `ledger_version`, so every event
+            // below them was emitted at or before it.
+            let deposits = get_all_events::<DepositEvent>(
+                self.api_client,
+                account,
+                "0x3::token::TokenStore",
+                "deposit_events",
+                data.deposit_events.counter.0,
+            ).await?;
+            let withdraws = get_all_events::<WithdrawEvent>(
+                self.api_client,
+                account,
+                "0x3::token::TokenStore",
+                "withdraw_events",
+                data.withdraw_events.counter.0,
+            ).await?;
+
+            let mut balances = BTreeMap::<(String, u64), (TokenId, i128)>::new();
+            for (token_id, amount) in deposits
+                .into_iter()
+                .map(|event| (event.data.id, event.data.amount.0 as i128))
+                .chain(withdraws.into_iter().map(|event| (event.data.id, -(event.data.amount.0 as i128))))
+            {
+                if !target.matches(&token_id) {
+                    continue;
+                }
+                let key = (token_id.token_data_id.name.clone(), token_id.property_version.0);
+                balances.entry(key).or_insert((token_id, 0)).1 += amount;
+            }
+
+            holders.extend(balances.into_values().filter(|(_, balance)| *balance > 0).map(
+                |(token_id, balance)| HolderBalance {
+                    owner: account,
+                    token_id,
+                    amount: balance as u64,
+                },
+            ));
+        }
+
+        Snapshot::new(target, ledger_version, holders)
+    }
+}
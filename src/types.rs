@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use aptos_sdk::{types::{account_address::AccountAddress}, rest_client::{aptos_api_types::U64}};
+#[derive(Clone, Debug)]
 pub struct TransactionOptions {
     pub max_gas_amount: u64,
 
@@ -23,21 +24,47 @@ impl Default for TransactionOptions {
     }
 }
 
-#[derive(Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct CollectionOptions {
     pub description_mutable: bool,
     pub uri_mutable: bool,
     pub supply_mutable: bool,
 }
 
-#[derive(Default)]
+/// Mirrors the `mutate_setting` vector of `create_token_script`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TokenMutability {
+    pub maximum: bool,
+    pub uri: bool,
+    pub royalty: bool,
+    pub description: bool,
+    pub properties: bool,
+}
+
+impl TokenMutability {
+    pub fn to_vec(&self) -> Vec<bool> {
+        vec![
+            self.maximum,
+            self.uri,
+            self.royalty,
+            self.description,
+            self.properties,
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct TokenProperty {
     pub keys: Vec<String>,
     pub values: Vec<String>,
     pub types: Vec<String>,
 }
 
-#[derive(Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct RoyaltyPoints {
     pub denominator: u64,
     pub numerator: u64,
@@ -60,6 +87,22 @@ pub struct ID {
     pub creation_num: U64,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MoveOption<T> {
+    pub vec: Vec<T>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimpleMapEntry<K, V> {
+    pub key: K,
+    pub value: V,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SimpleMap<K, V> {
+    pub data: Vec<SimpleMapEntry<K, V>>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Handle {
     pub handle: AccountAddress,
@@ -97,14 +140,14 @@ pub struct TokenDataStoreResources {
     pub token_data: Handle
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CollectionMutabilityConfig {
     pub description: bool,
     pub maximum: bool,
     pub uri: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CollectionData {
     pub name: String,
     pub description: String,
@@ -117,32 +160,152 @@ pub struct CollectionData {
 
 // NFT Token types
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Royalty {
+    pub royalty_points_numerator: U64,
+    pub royalty_points_denominator: U64,
+    pub payee_address: AccountAddress,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenMutabilityConfig {
+    pub maximum: bool,
+    pub uri: bool,
+    pub royalty: bool,
+    pub description: bool,
+    pub properties: bool,
+}
+
+/// A BCS-encoded property value, hex encoded, and its Move type name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PropertyValue {
+    pub value: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PropertyMap {
+    pub map: SimpleMap<String, PropertyValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenData {
     pub maximum: Option<U64>,
     pub largest_property_version: U64,
     pub supply: U64,
     pub uri: String,
-    // pub royalty
+    pub royalty: Royalty,
     pub name: String,
     pub description: String,
+    pub mutability_config: TokenMutabilityConfig,
+    pub default_properties: PropertyMap,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TokenDataId {
     pub creator: AccountAddress,
     pub collection: String,
     pub name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TokenId {
     pub token_data_id: TokenDataId,
     pub property_version: U64,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Token {
     pub id: TokenId,
     pub amount: U64,
-}
\ No newline at end of file
+}
+
+// Token events
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DepositEvent {
+    pub id: TokenId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WithdrawEvent {
+    pub id: TokenId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BurnTokenEvent {
+    pub id: TokenId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MutateTokenPropertyMapEvent {
+    pub old_id: TokenId,
+    pub new_id: TokenId,
+    pub keys: Vec<String>,
+    pub values: Vec<String>,
+    pub types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateCollectionEvent {
+    pub creator: AccountAddress,
+    pub collection_name: String,
+    pub uri: String,
+    pub description: String,
+    pub maximum: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateTokenDataEvent {
+    pub id: TokenDataId,
+    pub description: String,
+    pub maximum: U64,
+    pub uri: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MintTokenEvent {
+    pub id: TokenDataId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenOfferEvent {
+    pub to_address: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenClaimEvent {
+    pub to_address: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: U64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenCancelOfferEvent {
+    pub to_address: AccountAddress,
+    pub token_id: TokenId,
+    pub amount: U64,
+}
+
+// Token transfer types
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenOfferId {
+    pub to_addr: AccountAddress,
+    pub token_id: TokenId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingOffer {
+    pub sender: AccountAddress,
+    pub receiver: AccountAddress,
+    pub token: Token,
+}
@@ -0,0 +1,220 @@
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    types::{RoyaltyPoints, TokenProperty},
+};
+
+// Limits enforced by `0x3::token` and `0x3::property_map`.
+pub const MAX_COLLECTION_NAME_LENGTH: usize = 128;
+pub const MAX_TOKEN_NAME_LENGTH: usize = 128;
+pub const MAX_URI_LENGTH: usize = 512;
+pub const MAX_PROPERTY_MAP_SIZE: usize = 1000;
+pub const MAX_PROPERTY_NAME_LENGTH: usize = 128;
+pub const SUPPORTED_PROPERTY_TYPES: [&str; 7] = [
+    "bool",
+    "u8",
+    "u64",
+    "u128",
+    "address",
+    "0x1::string::String",
+    "vector<u8>",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    Empty { field: &'static str },
+    TooLong { field: &'static str, length: usize, max: usize },
+    ZeroAmount { field: &'static str },
+    RoyaltyNumeratorExceedsDenominator { numerator: u64, denominator: u64 },
+    ZeroRoyaltyDenominator { numerator: u64 },
+    SupplyExceedsMaximum { supply: u64, maximum: u64 },
+    TooManyProperties { count: usize, max: usize },
+    PropertyLengthMismatch { keys: usize, values: usize, types: usize },
+    DuplicatePropertyKey { key: String },
+    UnsupportedPropertyType { key: String, type_name: String },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Empty { field } => write!(f, "{} must not be empty", field),
+            ValidationError::TooLong { field, length, max } => {
+                write!(f, "{} is {} bytes long, the limit is {}", field, length, max)
+            }
+            ValidationError::ZeroAmount { field } => write!(f, "{} must be greater than 0", field),
+            ValidationError::RoyaltyNumeratorExceedsDenominator { numerator, denominator } => write!(
+                f,
+                "Royalty numerator {} exceeds denominator {}",
+                numerator, denominator
+            ),
+            ValidationError::ZeroRoyaltyDenominator { numerator } => write!(
+                f,
+                "Royalty denominator must not be 0 when numerator is {}",
+                numerator
+            ),
+            ValidationError::SupplyExceedsMaximum { supply, maximum } => {
+                write!(f, "Supply {} exceeds maximum {}", supply, maximum)
+            }
+            ValidationError::TooManyProperties { count, max } => {
+                write!(f, "{} properties given, the limit is {}", count, max)
+            }
+            ValidationError::PropertyLengthMismatch { keys, values, types } => write!(
+                f,
+                "Property keys ({}), values ({}) and types ({}) must have the same length",
+                keys, values, types
+            ),
+            ValidationError::DuplicatePropertyKey { key } => {
+                write!(f, "Property key `{}` is given more than once", key)
+            }
+            ValidationError::UnsupportedPropertyType { key, type_name } => {
+                write!(f, "Property `{}` has unsupported type `{}`", key, type_name)
+            }
+        }
+    }
+}
+
+/// Every limit violated by a request, so callers can report them at once
+/// instead of fixing one per submitted transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(pub Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.0
+    }
+
+    fn push(&mut self, error: ValidationError) {
+        self.0.push(error);
+    }
+
+    fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.0.iter().map(|e| e.to_string()).collect();
+        write!(f, "Validation failed: {}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Move only enforces the maximum, so existing names may be empty.
+fn check_length(errors: &mut ValidationErrors, field: &'static str, value: &str, max: usize) {
+    if value.len() > max {
+        errors.push(ValidationError::TooLong { field, length: value.len(), max });
+    }
+}
+
+/// Names given to new collections, tokens and properties.
+fn check_name(errors: &mut ValidationErrors, field: &'static str, value: &str, max: usize) {
+    if value.is_empty() {
+        errors.push(ValidationError::Empty { field });
+    } else {
+        check_length(errors, field, value, max);
+    }
+}
+
+fn check_uri(errors: &mut ValidationErrors, uri: &str) {
+    if uri.len() > MAX_URI_LENGTH {
+        errors.push(ValidationError::TooLong {
+            field: "uri",
+            length: uri.len(),
+            max: MAX_URI_LENGTH,
+        });
+    }
+}
+
+fn check_royalty(errors: &mut ValidationErrors, royalty: &RoyaltyPoints) {
+    if royalty.denominator == 0 && royalty.numerator != 0 {
+        errors.push(ValidationError::ZeroRoyaltyDenominator { numerator: royalty.numerator });
+    } else if royalty.numerator > royalty.denominator {
+        errors.push(ValidationError::RoyaltyNumeratorExceedsDenominator {
+            numerator: royalty.numerator,
+            denominator: royalty.denominator,
+        });
+    }
+}
+
+fn check_properties(errors: &mut ValidationErrors, properties: &TokenProperty) {
+    let (keys, values, types) = (
+        properties.keys.len(),
+        properties.values.len(),
+        properties.types.len(),
+    );
+    if keys != values || keys != types {
+        errors.push(ValidationError::PropertyLengthMismatch { keys, values, types });
+    }
+    if keys > MAX_PROPERTY_MAP_SIZE {
+        errors.push(ValidationError::TooManyProperties { count: keys, max: MAX_PROPERTY_MAP_SIZE });
+    }
+
+    let mut seen = HashSet::new();
+    for (key, type_name) in properties.keys.iter().zip(properties.types.iter()) {
+        check_name(errors, "property key", key, MAX_PROPERTY_NAME_LENGTH);
+        if !seen.insert(key) {
+            errors.push(ValidationError::DuplicatePropertyKey { key: key.clone() });
+        }
+        if !SUPPORTED_PROPERTY_TYPES.contains(&type_name.as_str()) {
+            errors.push(ValidationError::UnsupportedPropertyType {
+                key: key.clone(),
+                type_name: type_name.clone(),
+            });
+        }
+    }
+}
+
+pub fn validate_collection_request(request: &CreateCollectionRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_name(&mut errors, "collection name", &request.name, MAX_COLLECTION_NAME_LENGTH);
+    check_uri(&mut errors, &request.uri);
+    errors.into_result()
+}
+
+pub fn validate_token_request(request: &CreateTokenRequest) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_name(&mut errors, "collection name", &request.collection, MAX_COLLECTION_NAME_LENGTH);
+    check_name(&mut errors, "token name", &request.name, MAX_TOKEN_NAME_LENGTH);
+    check_uri(&mut errors, &request.uri);
+    // A maximum of 0 means unlimited supply.
+    if request.maximum != 0 && request.supply > request.maximum {
+        errors.push(ValidationError::SupplyExceedsMaximum {
+            supply: request.supply,
+            maximum: request.maximum,
+        });
+    }
+    check_royalty(&mut errors, &request.royalty);
+    check_properties(&mut errors, &request.properties);
+    errors.into_result()
+}
+
+/// Checks the token id and amount arguments shared by burn, offer, claim,
+/// transfer and swap entry functions. Pass `None` when there is no amount.
+/// Names may be empty, since they refer to tokens already on chain.
+pub fn validate_token_args(
+    collection_name: &str,
+    name: &str,
+    amount: Option<u64>,
+) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_length(&mut errors, "collection name", collection_name, MAX_COLLECTION_NAME_LENGTH);
+    check_length(&mut errors, "token name", name, MAX_TOKEN_NAME_LENGTH);
+    if amount == Some(0) {
+        errors.push(ValidationError::ZeroAmount { field: "amount" });
+    }
+    errors.into_result()
+}
+
+/// Checks properties set on held tokens with `mutate_token_properties`.
+pub fn validate_properties(properties: &TokenProperty) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_properties(&mut errors, properties);
+    errors.into_result()
+}
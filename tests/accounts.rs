@@ -0,0 +1,57 @@
+use aptos_sdk::types::account_address::AccountAddress;
+use rust_aptos_token_client::accounts::{private_key_from_seed, Keystore};
+
+// SLIP-0010 test vector 1 for ed25519.
+const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const VECTORS: [(&str, &str); 6] = [
+    ("m", "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"),
+    ("m/0'", "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"),
+    ("m/0'/1'", "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2"),
+    ("m/0'/1'/2'", "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9"),
+    ("m/0'/1'/2'/2'", "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662"),
+    (
+        "m/0'/1'/2'/2'/1000000000'",
+        "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+    ),
+];
+
+#[test]
+fn derives_slip10_test_vectors() {
+    let seed = hex::decode(SEED).unwrap();
+    for (path, expected) in VECTORS {
+        let private_key = private_key_from_seed(&seed, Some(path)).unwrap();
+        assert_eq!(hex::encode(private_key.to_bytes()), expected, "path {}", path);
+    }
+}
+
+#[test]
+fn rejects_unhardened_derivation_path() {
+    let seed = hex::decode(SEED).unwrap();
+    assert!(private_key_from_seed(&seed, Some("m/44'/637'/0")).is_err());
+    assert!(private_key_from_seed(&seed, Some("44'/637'")).is_err());
+}
+
+#[test]
+fn keystore_round_trip() {
+    let seed = hex::decode(SEED).unwrap();
+    let private_key = private_key_from_seed(&seed, None).unwrap();
+    let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+
+    let keystore = Keystore::encrypt(address, &private_key, "correct horse").unwrap();
+    let json = serde_json::to_string(&keystore).unwrap();
+    let keystore: Keystore = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(keystore.address, address);
+    assert_eq!(keystore.decrypt("correct horse").unwrap().to_bytes(), private_key.to_bytes());
+    assert!(keystore.decrypt("wrong horse").is_err());
+}
+
+#[test]
+fn keystore_rejects_bad_nonce_length() {
+    let seed = hex::decode(SEED).unwrap();
+    let private_key = private_key_from_seed(&seed, None).unwrap();
+    let mut keystore = Keystore::encrypt(AccountAddress::ONE, &private_key, "password").unwrap();
+
+    keystore.nonce = "0x0001".to_string();
+    assert!(keystore.decrypt("password").is_err());
+}
@@ -0,0 +1,262 @@
+#![cfg(feature = "indexer")]
+
+use aptos_sdk::{rest_client::Client as ApiClient, types::account_address::AccountAddress};
+use rust_aptos_token_client::indexer::Indexer;
+use serde_json::{json, Value};
+use url::Url;
+
+const CREATOR: &str = "0xc4ea702";
+const ALICE: &str = "0xa11ce";
+const BOB: &str = "0xb0b";
+
+fn token_id(property_version: &str) -> Value {
+    json!({
+        "token_data_id": { "creator": CREATOR, "collection": "Collection", "name": "Token #1" },
+        "property_version": property_version,
+    })
+}
+
+fn event(account: &str, event_type: &str, data: Value) -> Value {
+    json!({
+        "type": event_type,
+        "guid": { "account_address": account, "creation_number": "0" },
+        "sequence_number": "0",
+        "data": data,
+    })
+}
+
+fn transaction(version: u64, events: Vec<Value>) -> Value {
+    json!({
+        "type": "user_transaction",
+        "version": version.to_string(),
+        "success": true,
+        "events": events,
+        "changes": [],
+    })
+}
+
+fn open(api_client: &ApiClient) -> Indexer<'_> {
+    Indexer::open(api_client, ":memory:").unwrap()
+}
+
+fn address(address: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(address).unwrap()
+}
+
+#[test]
+fn tracks_ownership_from_deposits_and_withdraws() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+
+    indexer
+        .apply_transaction(&transaction(
+            10,
+            vec![event(ALICE, "0x3::token::DepositEvent", json!({ "id": token_id("0"), "amount": "3" }))],
+        ))
+        .unwrap();
+    indexer
+        .apply_transaction(&transaction(
+            11,
+            vec![
+                event(ALICE, "0x3::token::WithdrawEvent", json!({ "id": token_id("0"), "amount": "1" })),
+                event(BOB, "0x3::token::DepositEvent", json!({ "id": token_id("0"), "amount": "1" })),
+            ],
+        ))
+        .unwrap();
+
+    let alice = indexer.tokens_owned(address(ALICE)).unwrap();
+    assert_eq!(alice.len(), 1);
+    assert_eq!(alice[0].amount, 2);
+    assert_eq!(alice[0].token_id.token_data_id.name, "Token #1");
+
+    let holders = indexer.collection_holders(address(CREATOR), "Collection").unwrap();
+    assert_eq!(holders.len(), 2);
+}
+
+#[test]
+fn removes_claimed_offers() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let offer = json!({ "to_address": BOB, "token_id": token_id("0"), "amount": "1" });
+
+    indexer
+        .apply_transaction(&transaction(
+            10,
+            vec![event(ALICE, "0x3::token_transfers::TokenOfferEvent", offer.clone())],
+        ))
+        .unwrap();
+    let offers = indexer.pending_offers_to(address(BOB)).unwrap();
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0].sender, address(ALICE));
+    assert_eq!(offers[0].amount, 1);
+
+    indexer
+        .apply_transaction(&transaction(
+            11,
+            vec![event(ALICE, "0x3::token_transfers::TokenClaimEvent", offer)],
+        ))
+        .unwrap();
+    assert!(indexer.pending_offers_to(address(BOB)).unwrap().is_empty());
+}
+
+#[test]
+fn skips_failed_transactions() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let mut failed = transaction(
+        10,
+        vec![event(ALICE, "0x3::token::DepositEvent", json!({ "id": token_id("0"), "amount": "3" }))],
+    );
+    failed["success"] = json!(false);
+
+    indexer.apply_transaction(&failed).unwrap();
+    assert!(indexer.tokens_owned(address(ALICE)).unwrap().is_empty());
+}
+
+#[test]
+fn refuses_amounts_above_i64_max() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let amount = (i64::MAX as u64 + 1).to_string();
+
+    let result = indexer.apply_transaction(&transaction(
+        10,
+        vec![event(ALICE, "0x3::token::DepositEvent", json!({ "id": token_id("0"), "amount": amount }))],
+    ));
+    assert!(result.is_err());
+    assert!(indexer.tokens_owned(address(ALICE)).unwrap().is_empty());
+}
+
+#[test]
+fn refuses_property_versions_above_i64_max() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let property_version = u64::MAX.to_string();
+
+    let result = indexer.apply_transaction(&transaction(
+        10,
+        vec![event(ALICE, "0x3::token::DepositEvent", json!({ "id": token_id(&property_version), "amount": "1" }))],
+    ));
+    assert!(result.is_err());
+}
+
+fn write_resource(account: &str, resource_type: &str, data: Value) -> Value {
+    json!({
+        "type": "write_resource",
+        "address": account,
+        "data": { "type": resource_type, "data": data },
+    })
+}
+
+fn table_transaction(version: u64, events: Vec<Value>, changes: Vec<Value>) -> Value {
+    let mut transaction = transaction(version, events);
+    transaction["changes"] = Value::Array(changes);
+    transaction
+}
+
+fn count(indexer: &Indexer, sql: &str) -> i64 {
+    indexer.connection().query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn removes_cancelled_listings() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let listings = "0x3::token_coin_swap::TokenListings<0x1::aptos_coin::AptosCoin>";
+    let listing = json!({
+        "token_id": token_id("0"),
+        "amount": "1",
+        "min_price": "50",
+        "locked_until_secs": "0",
+        "coin_type_info": {
+            "account_address": "0x1",
+            "module_name": "0x6170746f735f636f696e",
+            "struct_name": "0x4170746f73436f696e",
+        },
+    });
+
+    indexer
+        .apply_transaction(&table_transaction(
+            10,
+            vec![event(ALICE, "0x3::token_coin_swap::TokenListingEvent", listing)],
+            vec![write_resource(ALICE, listings, json!({ "listings": { "handle": "0x11571" } }))],
+        ))
+        .unwrap();
+    assert_eq!(count(&indexer, "SELECT COUNT(*) FROM swap_listings"), 1);
+
+    // Cancelling emits no event, the listing is only deleted from the table.
+    let delete = |handle: &str| {
+        json!({
+            "type": "delete_table_item",
+            "handle": handle,
+            "key": "0x00",
+            "data": { "key": token_id("0"), "key_type": "0x3::token::TokenId" },
+        })
+    };
+    indexer.apply_transaction(&table_transaction(11, vec![], vec![delete("0x7ab1e")])).unwrap();
+    assert_eq!(count(&indexer, "SELECT COUNT(*) FROM swap_listings"), 1);
+
+    indexer
+        .apply_transaction(&table_transaction(12, vec![], vec![delete(&format!("0x{:0>64}", "11571"))]))
+        .unwrap();
+    assert_eq!(count(&indexer, "SELECT COUNT(*) FROM swap_listings"), 0);
+}
+
+#[test]
+fn tracks_collection_mutations() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let mut indexer = open(&api_client);
+    let collection_data = |description: &str, maximum: &str| {
+        json!({
+            "type": "write_table_item",
+            "handle": "0xc011",
+            "key": "0x00",
+            "value": "0x00",
+            "data": {
+                "key": "Collection",
+                "key_type": "0x1::string::String",
+                "value": {
+                    "name": "Collection",
+                    "description": description,
+                    "uri": "https://example.com",
+                    "supply": "0",
+                    "maximum": maximum,
+                    "mutability_config": { "description": true, "uri": true, "maximum": true },
+                },
+                "value_type": "0x3::token::CollectionData",
+            },
+        })
+    };
+    let created = json!({
+        "creator": CREATOR,
+        "collection_name": "Collection",
+        "uri": "https://example.com",
+        "description": "Before",
+        "maximum": "10",
+    });
+
+    // The table item comes before the resource that says whose table it is.
+    indexer
+        .apply_transaction(&table_transaction(
+            10,
+            vec![event(CREATOR, "0x3::token::CreateCollectionEvent", created)],
+            vec![
+                collection_data("Before", "10"),
+                write_resource(CREATOR, "0x3::token::Collections", json!({ "collection_data": { "handle": "0xc011" } })),
+            ],
+        ))
+        .unwrap();
+    indexer
+        .apply_transaction(&table_transaction(11, vec![], vec![collection_data("After", "20")]))
+        .unwrap();
+
+    let (description, maximum, last_version): (String, i64, i64) = indexer
+        .connection()
+        .query_row(
+            "SELECT description, maximum, last_version FROM collections WHERE creator = ?1 AND name = 'Collection'",
+            [address(CREATOR).to_hex_literal()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!((description.as_str(), maximum, last_version), ("After", 20, 11));
+}
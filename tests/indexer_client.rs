@@ -0,0 +1,228 @@
+use rust_aptos_token_client::{accounts::parse_address, indexer_client::IndexerClient};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+};
+use url::Url;
+
+/// Serves `responses` to consecutive requests and returns the requests'
+/// JSON bodies.
+async fn stub_server_sequence(responses: Vec<Value>) -> (Url, JoinHandle<Vec<Value>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}/v1/graphql", listener.local_addr().unwrap())).unwrap();
+
+    let handle = tokio::spawn(async move {
+        let mut bodies = Vec::new();
+        for response in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let body = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break request[header_end + 4..header_end + 4 + content_length].to_vec();
+                    }
+                }
+            };
+
+            let payload = response.to_string();
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                payload.len(),
+                payload
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            bodies.push(serde_json::from_slice(&body).unwrap());
+        }
+        bodies
+    });
+
+    (url, handle)
+}
+
+/// Serves `response` to a single request and returns the request's JSON body.
+async fn stub_server(response: Value) -> (Url, JoinHandle<Value>) {
+    let (url, handle) = stub_server_sequence(vec![response]).await;
+    (url, tokio::spawn(async move { handle.await.unwrap().remove(0) }))
+}
+
+fn ownership_row(name: &str) -> Value {
+    json!({
+        "owner_address": "0xa11ce",
+        "creator_address": "0xc0ffee",
+        "collection_name": "Collection",
+        "name": name,
+        "property_version": 0,
+        "amount": "1"
+    })
+}
+
+#[tokio::test]
+async fn current_token_ownerships() {
+    let (url, request) = stub_server(json!({
+        "data": {
+            "current_token_ownerships": [{
+                "owner_address": "0xa11ce",
+                "creator_address": "0xc0ffee",
+                "collection_name": "Collection",
+                "name": "Token #1",
+                "property_version": 0,
+                "amount": "2"
+            }]
+        }
+    }))
+    .await;
+
+    let tokens = IndexerClient::new(url)
+        .get_current_token_ownerships(parse_address("0xa11ce").unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].id.token_data_id.creator, parse_address("0xc0ffee").unwrap());
+    assert_eq!(tokens[0].id.token_data_id.collection, "Collection");
+    assert_eq!(tokens[0].id.token_data_id.name, "Token #1");
+    assert_eq!(tokens[0].id.property_version.0, 0);
+    assert_eq!(tokens[0].amount.0, 2);
+
+    let request = request.await.unwrap();
+    assert_eq!(request["variables"]["owner"], format!("0x{:0>64}", "a11ce"));
+    assert_eq!(request["variables"]["offset"], 0);
+}
+
+#[tokio::test]
+async fn collection_holders_are_paged() {
+    let (url, requests) = stub_server_sequence(vec![
+        json!({ "data": { "current_token_ownerships": [ownership_row("Token #1"), ownership_row("Token #2")] } }),
+        json!({ "data": { "current_token_ownerships": [ownership_row("Token #3")] } }),
+    ])
+    .await;
+
+    let holders = IndexerClient::new(url)
+        .with_page_size(2)
+        .get_collection_holders(parse_address("0xc0ffee").unwrap(), "Collection")
+        .await
+        .unwrap();
+
+    assert_eq!(holders.len(), 3);
+    assert_eq!(holders[2].token.id.token_data_id.name, "Token #3");
+
+    let requests = requests.await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["variables"]["creator"], format!("0x{:0>64}", "c0ffee"));
+    assert_eq!(requests[0]["variables"]["limit"], 2);
+    assert_eq!(requests[0]["variables"]["offset"], 0);
+    assert_eq!(requests[1]["variables"]["offset"], 2);
+}
+
+#[tokio::test]
+async fn collection_data() {
+    let (url, request) = stub_server(json!({
+        "data": {
+            "current_collection_datas": [{
+                "collection_name": "Collection",
+                "description": "A collection",
+                "metadata_uri": "https://example.com",
+                "supply": 3,
+                "maximum": 100,
+                "description_mutable": false,
+                "uri_mutable": true,
+                "maximum_mutable": false
+            }]
+        }
+    }))
+    .await;
+
+    let collection = IndexerClient::new(url)
+        .get_collection_data(parse_address("0xc0ffee").unwrap(), "Collection")
+        .await
+        .unwrap();
+
+    assert_eq!(collection.name, "Collection");
+    assert_eq!(collection.supply.0, 3);
+    assert_eq!(collection.maximum.0, 100);
+    assert!(collection.mutability_config.uri);
+    assert!(!collection.mutability_config.description);
+
+    let request = request.await.unwrap();
+    assert_eq!(request["variables"]["collection"], "Collection");
+}
+
+#[tokio::test]
+async fn graphql_errors_are_returned() {
+    let (url, _request) = stub_server(json!({
+        "errors": [{ "message": "field 'current_token_pending_claims' not found" }]
+    }))
+    .await;
+
+    let error = IndexerClient::new(url)
+        .get_pending_offers(parse_address("0xa11ce").unwrap())
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("current_token_pending_claims"));
+}
+
+fn pending_claim_row(name: &str) -> Value {
+    json!({
+        "from_address": "0xb0b",
+        "to_address": "0xa11ce",
+        "creator_address": "0xc0ffee",
+        "collection_name": "Collection",
+        "name": name,
+        "property_version": 0,
+        "amount": "1"
+    })
+}
+
+#[tokio::test]
+async fn pending_offers_are_paged() {
+    let (url, requests) = stub_server_sequence(vec![
+        json!({
+            "data": { "current_token_pending_claims": [pending_claim_row("Token #1"), pending_claim_row("Token #2")] }
+        }),
+        json!({ "data": { "current_token_pending_claims": [pending_claim_row("Token #3")] } }),
+    ])
+    .await;
+
+    let offers = IndexerClient::new(url)
+        .with_page_size(2)
+        .get_pending_offers(parse_address("0xa11ce").unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(offers.len(), 3);
+    assert_eq!(offers[2].sender, parse_address("0xb0b").unwrap());
+    assert_eq!(offers[2].token.id.token_data_id.name, "Token #3");
+
+    let requests = requests.await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["variables"]["receiver"], format!("0x{:0>64}", "a11ce"));
+    assert_eq!(requests[1]["variables"]["offset"], 2);
+}
+
+#[tokio::test]
+async fn inexact_numbers_are_refused() {
+    let mut row = pending_claim_row("Token #1");
+    row["amount"] = json!(1e20);
+    let (url, _request) = stub_server(json!({ "data": { "current_token_pending_claims": [row] } })).await;
+
+    let error = IndexerClient::new(url)
+        .get_pending_offers(parse_address("0xa11ce").unwrap())
+        .await
+        .unwrap_err();
+
+    assert!(format!("{:#}", error).contains("amount"), "{:#}", error);
+}
@@ -0,0 +1,65 @@
+use aptos_sdk::{
+    crypto::ed25519::Ed25519PrivateKey,
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{EntryFunction, RawTransaction, TransactionPayload},
+        LocalAccount,
+    },
+};
+use rust_aptos_token_client::multi_agent::MultiAgentTransaction;
+use serde_json::Value;
+
+fn account(address: &str, seed: u8) -> LocalAccount {
+    LocalAccount::new(
+        AccountAddress::from_hex_literal(address).unwrap(),
+        Ed25519PrivateKey::try_from(&[seed; 32][..]).unwrap(),
+        0,
+    )
+}
+
+fn transaction(sender: &LocalAccount, receiver: &LocalAccount) -> MultiAgentTransaction {
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::from_hex_literal("0x3").unwrap(), Identifier::new("token").unwrap()),
+        Identifier::new("direct_transfer_script").unwrap(),
+        vec![],
+        vec![],
+    ));
+    let raw_txn = RawTransaction::new(sender.address(), 0, payload, 2_000, 100, u64::MAX, ChainId::new(4));
+    MultiAgentTransaction::new(raw_txn, vec![receiver.address()])
+}
+
+#[tokio::test]
+async fn signed_transaction_round_trips_through_json() {
+    let sender = account("0xa11ce", 1);
+    let receiver = account("0xb0b", 2);
+    let mut txn = transaction(&sender, &receiver);
+    txn.sign(&sender).await.unwrap();
+
+    let parsed = MultiAgentTransaction::from_json(&txn.to_json().unwrap()).unwrap();
+    assert_eq!(parsed, txn);
+    assert_eq!(parsed.missing_signers(), vec![receiver.address()]);
+}
+
+#[tokio::test]
+async fn rejects_missing_signature_slots() {
+    let sender = account("0xa11ce", 1);
+    let receiver = account("0xb0b", 2);
+    let mut json: Value = serde_json::from_str(&transaction(&sender, &receiver).to_json().unwrap()).unwrap();
+    json["secondary_signatures"] = Value::Array(vec![]);
+
+    assert!(MultiAgentTransaction::from_json(&json.to_string()).is_err());
+}
+
+#[tokio::test]
+async fn rejects_signatures_over_another_transaction() {
+    let sender = account("0xa11ce", 1);
+    let receiver = account("0xb0b", 2);
+    let mut txn = transaction(&sender, &receiver);
+    txn.sign(&sender).await.unwrap();
+
+    let mut json: Value = serde_json::from_str(&txn.to_json().unwrap()).unwrap();
+    json["raw_txn"]["sequence_number"] = Value::from(1);
+    assert!(MultiAgentTransaction::from_json(&json.to_string()).is_err());
+}
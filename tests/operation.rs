@@ -0,0 +1,380 @@
+use aptos_sdk::{
+    rest_client::Client as ApiClient,
+    types::{account_address::AccountAddress, transaction::TransactionPayload},
+};
+use rust_aptos_token_client::{
+    explain::explain_committed_transaction,
+    multisig::multisig_payload_bytes,
+    network::TESTNET_CHAIN_ID,
+    operation::TokenOperation,
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    types::{RoyaltyPoints, TokenId, TokenProperty},
+    TokenClient,
+};
+use serde_json::{json, Value};
+use url::Url;
+
+fn address(hex: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(hex).unwrap()
+}
+
+fn creator() -> AccountAddress {
+    address("0xc0ffee")
+}
+
+fn decode(payload: &TransactionPayload) -> TokenOperation {
+    TokenOperation::from_payload(payload, &[]).unwrap()
+}
+
+fn assert_token_id(token_id: &TokenId, property_version: u64) {
+    assert_eq!(token_id.token_data_id.creator, creator());
+    assert_eq!(token_id.token_data_id.collection, "Collection");
+    assert_eq!(token_id.token_data_id.name, "Token");
+    assert_eq!(token_id.property_version.0, property_version);
+}
+
+fn with_client(test: impl FnOnce(&TokenClient)) {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    test(&TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID));
+}
+
+#[test]
+fn decodes_create_collection() {
+    with_client(|client| {
+        let request = CreateCollectionRequest::builder("Collection")
+            .description("Description")
+            .uri("https://example.com/collection")
+            .maximum(100)
+            .build()
+            .unwrap();
+
+        match decode(&client.create_collection_payload(&request).unwrap()) {
+            TokenOperation::CreateCollection { name, description, uri, maximum, mutability } => {
+                assert_eq!(name, "Collection");
+                assert_eq!(description, "Description");
+                assert_eq!(uri, "https://example.com/collection");
+                assert_eq!(maximum, 100);
+                assert_eq!(mutability.len(), 3);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_create_token() {
+    with_client(|client| {
+        let request = CreateTokenRequest::builder("Collection", "Token")
+            .description("Description")
+            .uri("https://example.com/token")
+            .supply(2)
+            .maximum(5)
+            .royalty(address("0xfee"), RoyaltyPoints { denominator: 100, numerator: 3 })
+            .property("color", "red", "0x1::string::String")
+            .build()
+            .unwrap();
+
+        match decode(&client.create_token_payload(creator(), &request).unwrap()) {
+            TokenOperation::CreateToken {
+                collection,
+                name,
+                description,
+                balance,
+                maximum,
+                uri,
+                royalty_payee,
+                royalty,
+                mutability,
+                properties,
+            } => {
+                assert_eq!(collection, "Collection");
+                assert_eq!(name, "Token");
+                assert_eq!(description, "Description");
+                assert_eq!(balance, 2);
+                assert_eq!(maximum, 5);
+                assert_eq!(uri, "https://example.com/token");
+                assert_eq!(royalty_payee, address("0xfee"));
+                assert_eq!((royalty.numerator, royalty.denominator), (3, 100));
+                assert_eq!(mutability.len(), 5);
+                assert_eq!(properties.keys, vec!["color"]);
+                assert_eq!(properties.values, vec!["red"]);
+                assert_eq!(properties.types, vec!["0x1::string::String"]);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_mint_and_burn() {
+    with_client(|client| {
+        match decode(&client.mint_token_payload(creator(), "Collection", "Token", 4).unwrap()) {
+            TokenOperation::Mint { creator: minted_by, collection, name, amount } => {
+                assert_eq!(minted_by, creator());
+                assert_eq!(collection, "Collection");
+                assert_eq!(name, "Token");
+                assert_eq!(amount, 4);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+
+        match decode(&client.burn_token_payload(creator(), "Collection", "Token", 2, 7).unwrap()) {
+            TokenOperation::Burn { token_id, amount } => {
+                assert_token_id(&token_id, 7);
+                assert_eq!(amount, 2);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_mutate_token_properties() {
+    with_client(|client| {
+        let properties = TokenProperty {
+            keys: vec!["level".to_string()],
+            values: vec!["3".to_string()],
+            types: vec!["0x1::string::String".to_string()],
+        };
+        let payload = client
+            .mutate_token_properties_payload(address("0xa11ce"), creator(), "Collection", "Token", 1, 1, &properties)
+            .unwrap();
+
+        match decode(&payload) {
+            TokenOperation::MutateProperties { token_owner, token_id, amount, properties } => {
+                assert_eq!(token_owner, address("0xa11ce"));
+                assert_token_id(&token_id, 1);
+                assert_eq!(amount, 1);
+                assert_eq!(properties.keys, vec!["level"]);
+                assert_eq!(properties.values, vec!["3"]);
+                assert_eq!(properties.types, vec!["0x1::string::String"]);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_offer_claim_and_cancel() {
+    with_client(|client| {
+        let payload = client
+            .offer_token_payload(address("0xb0b"), creator(), "Collection", "Token", 3, 0)
+            .unwrap();
+        match decode(&payload) {
+            TokenOperation::Offer { receiver, token_id, amount } => {
+                assert_eq!(receiver, address("0xb0b"));
+                assert_token_id(&token_id, 0);
+                assert_eq!(amount, 3);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+
+        let payload = client
+            .claim_token_payload(address("0xa11ce"), creator(), "Collection", "Token", 0)
+            .unwrap();
+        match decode(&payload) {
+            TokenOperation::Claim { sender, token_id } => {
+                assert_eq!(sender, address("0xa11ce"));
+                assert_token_id(&token_id, 0);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+
+        let payload = client
+            .cancel_token_offer_payload(address("0xb0b"), creator(), "Collection", "Token", 0)
+            .unwrap();
+        match decode(&payload) {
+            TokenOperation::CancelOffer { receiver, token_id } => {
+                assert_eq!(receiver, address("0xb0b"));
+                assert_token_id(&token_id, 0);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_transfers() {
+    with_client(|client| {
+        let payload = client
+            .direct_transfer_token_payload(creator(), "Collection", "Token", 2, 0)
+            .unwrap();
+        match TokenOperation::from_payload(&payload, &[address("0xb0b")]).unwrap() {
+            TokenOperation::Transfer { receiver, token_id, amount } => {
+                assert_eq!(receiver, Some(address("0xb0b")));
+                assert_token_id(&token_id, 0);
+                assert_eq!(amount, 2);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+        assert!(matches!(decode(&payload), TokenOperation::Transfer { receiver: None, .. }));
+
+        let payload = client
+            .transfer_with_opt_in_payload(address("0xb0b"), creator(), "Collection", "Token", 2, 0)
+            .unwrap();
+        match decode(&payload) {
+            TokenOperation::Transfer { receiver, token_id, amount } => {
+                assert_eq!(receiver, Some(address("0xb0b")));
+                assert_token_id(&token_id, 0);
+                assert_eq!(amount, 2);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+
+        assert!(matches!(
+            decode(&client.opt_in_direct_transfer_payload(true)),
+            TokenOperation::OptInDirectTransfer { opt_in: true }
+        ));
+    });
+}
+
+#[test]
+fn decodes_list_token_for_swap() {
+    with_client(|client| {
+        let payload = client
+            .list_token_for_swap_payload(creator(), "Collection", "Token", 2, 50, 1_700_000_000, 0, "0x1::aptos_coin::AptosCoin")
+            .unwrap();
+
+        match decode(&payload) {
+            TokenOperation::List { token_id, amount, min_coin_per_token, locked_until_secs, coin_type } => {
+                assert_token_id(&token_id, 0);
+                assert_eq!(amount, 2);
+                assert_eq!(min_coin_per_token, 50);
+                assert_eq!(locked_until_secs, 1_700_000_000);
+                assert!(coin_type.ends_with("::aptos_coin::AptosCoin"), "{}", coin_type);
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn decodes_multisig_proposal() {
+    with_client(|client| {
+        let offer = client
+            .offer_token_payload(address("0xb0b"), creator(), "Collection", "Token", 1, 0)
+            .unwrap();
+        let payload = client.propose_multisig_transaction_payload(address("0x5afe"), &offer).unwrap();
+
+        match decode(&payload) {
+            TokenOperation::MultisigProposal { multisig_account, proposal } => {
+                assert_eq!(multisig_account, address("0x5afe"));
+                assert!(matches!(*proposal, TokenOperation::Offer { amount: 1, .. }));
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+/// A committed user transaction as the REST API returns it, which renders
+/// u64 arguments as strings and `vector<u8>` arguments as hex.
+fn committed(payload: Value) -> Value {
+    json!({
+        "type": "user_transaction",
+        "hash": "0x01",
+        "version": "42",
+        "success": true,
+        "vm_status": "Executed successfully",
+        "sender": "0xa11ce",
+        "sequence_number": "7",
+        "payload": payload,
+        "signature": { "type": "ed25519_signature" },
+    })
+}
+
+#[test]
+fn decodes_json_u64_and_hex_arguments() {
+    let payload = json!({
+        "type": "entry_function_payload",
+        "function": "0x3::token::mutate_token_properties",
+        "type_arguments": [],
+        "arguments": [
+            "0xa11ce",
+            "0xc0ffee",
+            "Collection",
+            "Token",
+            "18446744073709551615",
+            "1",
+            ["level"],
+            ["0x33"],
+            ["0x1::string::String"],
+        ],
+    });
+
+    match TokenOperation::from_json_payload(&payload, &[]).unwrap() {
+        TokenOperation::MutateProperties { token_owner, token_id, amount, properties } => {
+            assert_eq!(token_owner, address("0xa11ce"));
+            assert_eq!(token_id.token_data_id.creator, creator());
+            assert_eq!(token_id.property_version.0, u64::MAX);
+            assert_eq!(amount, 1);
+            assert_eq!(properties.keys, vec!["level"]);
+            assert_eq!(properties.values, vec!["3"]);
+            assert_eq!(properties.types, vec!["0x1::string::String"]);
+        }
+        operation => panic!("Unexpected {:?}", operation),
+    }
+
+    let mut invalid_hex = payload;
+    invalid_hex["arguments"][7] = json!(["0xzz"]);
+    assert!(TokenOperation::from_json_payload(&invalid_hex, &[]).is_err());
+}
+
+#[test]
+fn decodes_json_multisig_proposal() {
+    with_client(|client| {
+        let offer = client
+            .offer_token_payload(address("0xb0b"), creator(), "Collection", "Token", 2, 0)
+            .unwrap();
+        let payload = json!({
+            "type": "entry_function_payload",
+            "function": "0x1::multisig_account::create_transaction",
+            "type_arguments": [],
+            "arguments": ["0x5afe", format!("0x{}", hex::encode(multisig_payload_bytes(&offer).unwrap()))],
+        });
+
+        match TokenOperation::from_json_payload(&payload, &[]).unwrap() {
+            TokenOperation::MultisigProposal { multisig_account, proposal } => {
+                assert_eq!(multisig_account, address("0x5afe"));
+                match *proposal {
+                    TokenOperation::Offer { receiver, token_id, amount } => {
+                        assert_eq!(receiver, address("0xb0b"));
+                        assert_token_id(&token_id, 0);
+                        assert_eq!(amount, 2);
+                    }
+                    operation => panic!("Unexpected {:?}", operation),
+                }
+            }
+            operation => panic!("Unexpected {:?}", operation),
+        }
+    });
+}
+
+#[test]
+fn explains_committed_transactions() {
+    let mut transaction = committed(json!({
+        "type": "entry_function_payload",
+        "function": "0x3::token::direct_transfer_script",
+        "type_arguments": [],
+        "arguments": ["0xc0ffee", "Collection", "Token", "0", "2"],
+    }));
+    transaction["signature"] = json!({ "type": "multi_agent_signature", "secondary_signer_addresses": ["0xb0b"] });
+
+    let explanation = explain_committed_transaction(&transaction).unwrap();
+    assert_eq!(explanation.sender, address("0xa11ce"));
+    assert_eq!(explanation.sequence_number, 7);
+    assert_eq!(explanation.secondary_signers, vec![address("0xb0b")]);
+    match &explanation.operation {
+        TokenOperation::Transfer { receiver, token_id, amount } => {
+            assert_eq!(*receiver, Some(address("0xb0b")));
+            assert_token_id(token_id, 0);
+            assert_eq!(*amount, 2);
+        }
+        operation => panic!("Unexpected {:?}", operation),
+    }
+    let committed_status = explanation.committed.as_ref().unwrap();
+    assert_eq!((committed_status.version, committed_status.success), (42, true));
+    assert!(explanation.summary.ends_with("committed at version 42"), "{}", explanation.summary);
+
+    transaction["type"] = json!("genesis_transaction");
+    assert!(explain_committed_transaction(&transaction).is_err());
+}
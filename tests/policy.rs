@@ -0,0 +1,318 @@
+use std::{thread, time::Duration};
+
+use anyhow::{bail, Result};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        PrivateKey,
+    },
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::{aptos_api_types::U64, Client as ApiClient},
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, RawTransaction, RawTransactionWithData, TransactionPayload},
+    },
+};
+use async_trait::async_trait;
+use rust_aptos_token_client::{
+    network::{MAINNET_CHAIN_ID, TESTNET_CHAIN_ID},
+    operation::{OperationKind, TokenOperation},
+    policy::{
+        AllowedReceivers, DenyUnknown, ForbidBurns, MainnetApproval, MaxTransferAmount, PolicyContext, PolicyError,
+        PolicyRule, RateLimit, RoyaltyCap, SigningPolicy,
+    },
+    signer::TransactionSigner,
+    types::{RoyaltyPoints, TokenDataId, TokenId, TokenProperty},
+    TokenClient,
+};
+use url::Url;
+
+fn address(hex: &str) -> AccountAddress {
+    AccountAddress::from_hex_literal(hex).unwrap()
+}
+
+fn context(chain_id: u8) -> PolicyContext {
+    PolicyContext {
+        sender: address("0xa11ce"),
+        chain_id,
+    }
+}
+
+fn token_id() -> TokenId {
+    TokenId {
+        token_data_id: TokenDataId {
+            creator: address("0xc0ffee"),
+            collection: "Collection".to_string(),
+            name: "Token".to_string(),
+        },
+        property_version: U64(0),
+    }
+}
+
+fn offer(receiver: &str, amount: u64) -> TokenOperation {
+    TokenOperation::Offer {
+        receiver: address(receiver),
+        token_id: token_id(),
+        amount,
+    }
+}
+
+fn burn(amount: u64) -> TokenOperation {
+    TokenOperation::Burn { token_id: token_id(), amount }
+}
+
+fn create_token(royalty: RoyaltyPoints) -> TokenOperation {
+    TokenOperation::CreateToken {
+        collection: "Collection".to_string(),
+        name: "Token".to_string(),
+        description: String::new(),
+        balance: 1,
+        maximum: 1,
+        uri: String::new(),
+        royalty_payee: address("0xc0ffee"),
+        royalty,
+        mutability: vec![false; 5],
+        properties: TokenProperty::default(),
+    }
+}
+
+/// Fails every signature, like a remote signer that is down.
+struct FailingSigner;
+
+#[async_trait]
+impl TransactionSigner for FailingSigner {
+    fn address(&self) -> AccountAddress {
+        address("0xa11ce")
+    }
+
+    fn public_key(&self) -> Ed25519PublicKey {
+        Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap().public_key()
+    }
+
+    async fn next_sequence_number(&mut self) -> Result<u64> {
+        Ok(0)
+    }
+
+    async fn sign_raw_transaction(&self, _raw_txn: &RawTransaction) -> Result<Ed25519Signature> {
+        bail!("Signer is down")
+    }
+
+    async fn sign_multi_agent_transaction(&self, _message: &RawTransactionWithData) -> Result<Ed25519Signature> {
+        bail!("Signer is down")
+    }
+}
+
+fn unknown_payload() -> TransactionPayload {
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(AccountAddress::ONE, Identifier::new("aptos_account").unwrap()),
+        Identifier::new("transfer").unwrap(),
+        vec![],
+        vec![],
+    ))
+}
+
+#[test]
+fn allowed_receivers_checks_offers_and_transfers() {
+    let rule = AllowedReceivers([address("0xb0b")].into_iter().collect());
+
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)).is_ok());
+    assert_eq!(
+        rule.check(&context(TESTNET_CHAIN_ID), &offer("0xe5e", 1)),
+        Err(PolicyError::ReceiverNotAllowed { receiver: address("0xe5e") })
+    );
+    let transfer = TokenOperation::Transfer {
+        receiver: None,
+        token_id: token_id(),
+        amount: 1,
+    };
+    assert_eq!(rule.check(&context(TESTNET_CHAIN_ID), &transfer), Err(PolicyError::UnknownReceiver));
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &burn(1)).is_ok());
+}
+
+#[test]
+fn max_transfer_amount_is_inclusive() {
+    let rule = MaxTransferAmount(10);
+
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 10)).is_ok());
+    assert_eq!(
+        rule.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 11)),
+        Err(PolicyError::AmountExceedsLimit { amount: 11, max: 10 })
+    );
+}
+
+#[test]
+fn forbid_burns_rejects_only_burns() {
+    assert_eq!(ForbidBurns.check(&context(TESTNET_CHAIN_ID), &burn(1)), Err(PolicyError::BurnForbidden));
+    assert!(ForbidBurns.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)).is_ok());
+}
+
+#[test]
+fn royalty_cap_compares_exact_fractions() {
+    let royalty = |numerator, denominator| create_token(RoyaltyPoints { denominator, numerator });
+    let cap = RoyaltyCap(250);
+
+    assert!(cap.check(&context(TESTNET_CHAIN_ID), &royalty(1, 40)).is_ok());
+    assert!(cap.check(&context(TESTNET_CHAIN_ID), &royalty(250, 10_000)).is_ok());
+    assert!(cap.check(&context(TESTNET_CHAIN_ID), &royalty(251, 10_000)).is_err());
+    assert!(cap.check(&context(TESTNET_CHAIN_ID), &royalty(5, 0)).is_ok());
+    // 1/3 is 3333.33... basis points.
+    assert!(RoyaltyCap(3333).check(&context(TESTNET_CHAIN_ID), &royalty(1, 3)).is_err());
+    assert!(RoyaltyCap(3334).check(&context(TESTNET_CHAIN_ID), &royalty(1, 3)).is_ok());
+    assert!(RoyaltyCap(0).check(&context(TESTNET_CHAIN_ID), &royalty(u64::MAX, 1)).is_err());
+}
+
+#[test]
+fn mainnet_approval_asks_only_on_mainnet() {
+    let rule = MainnetApproval::new([OperationKind::Burn], |_, operation| {
+        matches!(operation, TokenOperation::Burn { amount, .. } if *amount <= 1)
+    });
+
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &burn(5)).is_ok());
+    assert!(rule.check(&context(MAINNET_CHAIN_ID), &burn(1)).is_ok());
+    assert_eq!(
+        rule.check(&context(MAINNET_CHAIN_ID), &burn(5)),
+        Err(PolicyError::ApprovalDenied { kind: OperationKind::Burn })
+    );
+    assert!(rule.check(&context(MAINNET_CHAIN_ID), &offer("0xb0b", 5)).is_ok());
+}
+
+#[test]
+fn rate_limit_slides_over_its_window() {
+    let rule = RateLimit::new(OperationKind::Burn, 2).with_window(Duration::from_millis(200));
+
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &burn(1)).is_ok());
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &burn(1)).is_ok());
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)).is_ok());
+    assert_eq!(
+        rule.check(&context(TESTNET_CHAIN_ID), &burn(1)),
+        Err(PolicyError::RateLimited { kind: OperationKind::Burn, max_per_hour: 2 })
+    );
+
+    thread::sleep(Duration::from_millis(250));
+    assert!(rule.check(&context(TESTNET_CHAIN_ID), &burn(1)).is_ok());
+}
+
+#[test]
+fn rate_limit_slot_is_released_when_a_later_rule_rejects() {
+    let policy = SigningPolicy::new()
+        .rate_limit(OperationKind::Offer, 1)
+        .allowed_receivers([address("0xb0b")]);
+
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &offer("0xe5e", 1)).is_err());
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)).is_ok());
+    assert!(matches!(
+        policy.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)),
+        Err(PolicyError::RateLimited { .. })
+    ));
+
+    policy.release(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1));
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &offer("0xb0b", 1)).is_ok());
+}
+
+#[test]
+fn deny_unknown_rejects_other_operations() {
+    let other = TokenOperation::from_payload(&unknown_payload(), &[]).unwrap();
+
+    assert!(matches!(
+        DenyUnknown.check(&context(TESTNET_CHAIN_ID), &other),
+        Err(PolicyError::UnknownOperation { .. })
+    ));
+    assert!(DenyUnknown.check(&context(TESTNET_CHAIN_ID), &burn(1)).is_ok());
+}
+
+#[test]
+fn multisig_proposals_are_checked_as_the_proposed_operation() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let client = TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID);
+    let multisig_account = address("0x5afe");
+    let burn = client
+        .burn_token_payload(address("0xc0ffee"), "Collection", "Token", 1, 0)
+        .unwrap();
+
+    let proposal = client.propose_multisig_transaction_payload(multisig_account, &burn).unwrap();
+    let operation = TokenOperation::from_payload(&proposal, &[]).unwrap();
+    assert_eq!(
+        SigningPolicy::new().forbid_burns().check(&context(TESTNET_CHAIN_ID), &operation),
+        Err(PolicyError::BurnForbidden)
+    );
+
+    let proposal = client
+        .propose_multisig_transaction_payload(multisig_account, &unknown_payload())
+        .unwrap();
+    let operation = TokenOperation::from_payload(&proposal, &[]).unwrap();
+    assert!(matches!(
+        SigningPolicy::new().deny_unknown().check(&context(TESTNET_CHAIN_ID), &operation),
+        Err(PolicyError::UnknownOperation { .. })
+    ));
+}
+
+#[test]
+fn unsigned_transactions_are_checked() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let client = TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID).with_policy(SigningPolicy::new().forbid_burns());
+    let burn = client
+        .burn_token_payload(address("0xc0ffee"), "Collection", "Token", 1, 0)
+        .unwrap();
+    let mint = client.mint_token_payload(address("0xc0ffee"), "Collection", "Token", 1).unwrap();
+
+    let error = client
+        .build_unsigned_transaction(address("0xc0ffee"), 0, u64::MAX, burn, None)
+        .unwrap_err();
+    assert_eq!(error.downcast_ref::<PolicyError>(), Some(&PolicyError::BurnForbidden));
+    assert!(client
+        .build_unsigned_transaction(address("0xc0ffee"), 0, u64::MAX, mint, None)
+        .is_ok());
+}
+
+#[test]
+fn multisig_approvals_are_checked_as_the_proposed_operation() {
+    let vote = |approve, proposal: Option<TokenOperation>| TokenOperation::MultisigVote {
+        multisig_account: address("0x5afe"),
+        sequence_number: 3,
+        approve,
+        proposal: proposal.map(Box::new),
+    };
+    let policy = SigningPolicy::new().deny_unknown().forbid_burns();
+
+    assert_eq!(
+        policy.check(&context(TESTNET_CHAIN_ID), &vote(true, Some(burn(1)))),
+        Err(PolicyError::BurnForbidden)
+    );
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &vote(true, Some(offer("0xb0b", 1)))).is_ok());
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &vote(false, Some(burn(1)))).is_ok());
+    assert_eq!(
+        policy.check(&context(TESTNET_CHAIN_ID), &vote(true, None)),
+        Err(PolicyError::UnknownProposal { multisig_account: address("0x5afe"), sequence_number: 3 })
+    );
+    assert!(policy.check(&context(TESTNET_CHAIN_ID), &vote(false, None)).is_ok());
+}
+
+#[test]
+fn multisig_votes_decode_without_their_proposal() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let client = TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID);
+
+    let approve = client.approve_multisig_transaction_payload(address("0x5afe"), 3);
+    assert!(matches!(
+        TokenOperation::from_payload(&approve, &[]).unwrap(),
+        TokenOperation::MultisigVote { sequence_number: 3, approve: true, proposal: None, .. }
+    ));
+    let reject = client.reject_multisig_transaction_payload(address("0x5afe"), 3);
+    assert!(matches!(
+        TokenOperation::from_payload(&reject, &[]).unwrap(),
+        TokenOperation::MultisigVote { sequence_number: 3, approve: false, proposal: None, .. }
+    ));
+}
+
+#[tokio::test]
+async fn rate_limit_slot_is_released_when_signing_fails() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let client = TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID)
+        .with_policy(SigningPolicy::new().rate_limit(OperationKind::Mint, 1));
+    let mint = client.mint_token_payload(address("0xa11ce"), "Collection", "Token", 1).unwrap();
+
+    assert!(client.submit_payload(&mut FailingSigner, mint.clone(), None).await.is_err());
+    assert!(client
+        .build_unsigned_transaction(address("0xa11ce"), 0, u64::MAX, mint, None)
+        .is_ok());
+}
@@ -0,0 +1,88 @@
+use aptos_sdk::{rest_client::aptos_api_types::U64, types::account_address::AccountAddress};
+use rust_aptos_token_client::{
+    snapshot::{verify_proof, HolderBalance, Snapshot, SnapshotTarget},
+    types::{TokenDataId, TokenId},
+};
+
+fn target() -> SnapshotTarget {
+    SnapshotTarget::Collection {
+        creator: AccountAddress::from_hex_literal("0xc0ffee").unwrap(),
+        collection: "Collection".to_string(),
+    }
+}
+
+fn holders(count: u64) -> Vec<HolderBalance> {
+    (0..count)
+        .map(|index| HolderBalance {
+            owner: AccountAddress::from_hex_literal(&format!("0x{:x}", 0x1000 + index)).unwrap(),
+            token_id: TokenId {
+                token_data_id: TokenDataId {
+                    creator: AccountAddress::from_hex_literal("0xc0ffee").unwrap(),
+                    collection: "Collection".to_string(),
+                    name: format!("Token #{}", index),
+                },
+                property_version: U64(0),
+            },
+            amount: index + 1,
+        })
+        .collect()
+}
+
+fn decode(hash: &str) -> [u8; 32] {
+    hex::decode(hash).unwrap().try_into().unwrap()
+}
+
+fn assert_every_proof_verifies(snapshot: &Snapshot) {
+    let root = decode(&snapshot.merkle_root);
+    for entry in &snapshot.entries {
+        let proof: Vec<[u8; 32]> = entry.proof.iter().map(|hash| decode(hash)).collect();
+        assert!(verify_proof(entry.holder.leaf_hash().unwrap(), &proof, root));
+    }
+}
+
+#[test]
+fn empty_snapshot_has_zero_root() {
+    let snapshot = Snapshot::new(target(), 7, vec![]).unwrap();
+    assert!(snapshot.entries.is_empty());
+    assert_eq!(snapshot.merkle_root, hex::encode([0u8; 32]));
+}
+
+#[test]
+fn single_leaf_is_the_root() {
+    let snapshot = Snapshot::new(target(), 7, holders(1)).unwrap();
+    assert_eq!(snapshot.merkle_root, hex::encode(snapshot.entries[0].holder.leaf_hash().unwrap()));
+    assert!(snapshot.entries[0].proof.is_empty());
+    assert_every_proof_verifies(&snapshot);
+}
+
+#[test]
+fn every_leaf_has_a_valid_proof() {
+    for count in [2, 3, 4, 5, 7, 8, 13] {
+        let snapshot = Snapshot::new(target(), 7, holders(count)).unwrap();
+        assert_eq!(snapshot.entries.len() as u64, count);
+        assert_every_proof_verifies(&snapshot);
+    }
+}
+
+#[test]
+fn proof_fails_for_a_changed_amount() {
+    let snapshot = Snapshot::new(target(), 7, holders(5)).unwrap();
+    let entry = &snapshot.entries[2];
+    let proof: Vec<[u8; 32]> = entry.proof.iter().map(|hash| decode(hash)).collect();
+    let mut holder = entry.holder.clone();
+    holder.amount += 1;
+
+    assert!(!verify_proof(holder.leaf_hash().unwrap(), &proof, decode(&snapshot.merkle_root)));
+}
+
+#[test]
+fn csv_records_root_and_ledger_version() {
+    let snapshot = Snapshot::new(target(), 7, holders(3)).unwrap();
+    let csv = snapshot.to_csv();
+    let mut lines = csv.lines();
+
+    assert_eq!(lines.next().unwrap(), format!("# merkle_root: {}", snapshot.merkle_root));
+    assert_eq!(lines.next().unwrap(), "# ledger_version: 7");
+    assert_eq!(lines.next().unwrap(), "owner,creator,collection,name,property_version,amount,proof");
+    assert_eq!(lines.count(), 3);
+}
@@ -0,0 +1,53 @@
+use aptos_sdk::{rest_client::Client as ApiClient, types::account_address::AccountAddress};
+use rust_aptos_token_client::{
+    network::TESTNET_CHAIN_ID,
+    requests::{CreateCollectionRequest, CreateTokenRequest},
+    types::TokenProperty,
+    validation::{validate_token_args, ValidationError},
+    TokenClient,
+};
+use url::Url;
+
+#[test]
+fn existing_tokens_may_have_empty_names() {
+    assert!(validate_token_args("", "", Some(1)).is_ok());
+    assert_eq!(
+        validate_token_args("", "", Some(0)).unwrap_err().errors(),
+        &[ValidationError::ZeroAmount { field: "amount" }]
+    );
+}
+
+#[test]
+fn new_collections_and_tokens_need_names() {
+    assert!(CreateCollectionRequest::builder("").build().is_err());
+    assert!(CreateTokenRequest::builder("Collection", "").build().is_err());
+}
+
+#[test]
+fn mutated_properties_are_validated() {
+    let api_client = ApiClient::new(Url::parse("http://127.0.0.1:1").unwrap());
+    let client = TokenClient::with_chain_id(&api_client, TESTNET_CHAIN_ID);
+    let owner = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+    let mutate = |properties: &TokenProperty| {
+        client.mutate_token_properties_payload(owner, owner, "Collection", "Token", 0, 1, properties)
+    };
+
+    let valid = TokenProperty {
+        keys: vec!["level".to_string()],
+        values: vec!["3".to_string()],
+        types: vec!["0x1::string::String".to_string()],
+    };
+    assert!(mutate(&valid).is_ok());
+
+    let mismatched = TokenProperty {
+        values: vec![],
+        ..valid.clone()
+    };
+    assert!(mutate(&mismatched).is_err());
+
+    let unsupported = TokenProperty {
+        types: vec!["0x1::object::Object".to_string()],
+        ..valid
+    };
+    assert!(mutate(&unsupported).is_err());
+}